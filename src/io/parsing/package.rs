@@ -12,11 +12,23 @@ use semver::VersionReq;
 use std::collections::HashMap;
 use std::path::PathBuf;
 
-pub fn get_packages(path: impl Into<PathBuf>) -> Result<(Vec<Package>, Option<Package>, PathBuf)> {
-    let metadata = cargo_metadata::MetadataCommand::new()
+/// `toolchain` is used through rustup, `options` like `--offline` are passed to `cargo metadata`
+pub fn get_packages(
+    path: impl Into<PathBuf>,
+    toolchain: Option<&str>,
+    options: &[String],
+) -> Result<(Vec<Package>, Option<Package>, PathBuf)> {
+    let mut command = cargo_metadata::MetadataCommand::new();
+    command
         .current_dir(path)
         .features(CargoOpt::AllFeatures)
-        .exec()?;
+        .other_options(options);
+
+    if let Some(toolchain) = toolchain {
+        command.env("RUSTUP_TOOLCHAIN", toolchain);
+    }
+
+    let metadata = command.exec()?;
 
     let metadata_packages: HashMap<PackageId, cargo_metadata::Package> = metadata
         .packages
@@ -64,8 +76,7 @@ pub fn get_package_from_version<'a>(
     packages: &'a HashMap<PackageId, cargo_metadata::Package>,
 ) -> Result<&'a cargo_metadata::Package> {
     packages
-        .iter()
-        .map(|(_, package)| package)
+        .values()
         .filter(|package| package.name == name)
         .find(|package| version_req.matches(&package.version) || version_req.to_string() == "*")
         .context(format!(
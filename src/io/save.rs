@@ -1,9 +1,11 @@
-use crate::io::util::{get_mut_item_from_doc, toml_document_from_path};
+use crate::io::util::{get_item_from_doc, get_mut_item_from_doc, toml_document_from_path};
 use crate::project::dependency::util::get_path;
+use crate::project::dependency::DependencyType;
 use crate::project::document::Document;
+use cargo_platform::Platform;
 use color_eyre::eyre::{ContextCompat, Error};
 use std::fs;
-use toml_edit::{Array, Formatted, InlineTable, Item, Value};
+use toml_edit::{Array, DocumentMut, Formatted, InlineTable, Item, Table, Value};
 
 pub fn save_dependency(
     document: &mut Document,
@@ -107,3 +109,189 @@ pub fn save_dependency(
 
     fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
 }
+
+/// adds the features to `<table_path>.<dependency_name>` so prune never tests them again
+pub fn save_kept_features(
+    manifest_path: &str,
+    table_path: &str,
+    dependency_name: &str,
+    features: &[&String],
+) -> color_eyre::Result<()> {
+    let mut doc = toml_document_from_path(manifest_path)?;
+
+    let mut item = doc.as_item_mut();
+
+    for key in table_path.split('.') {
+        let table = item
+            .as_table_like_mut()
+            .context(format!("could not parse {} as a table", table_path))?;
+
+        if !table.contains_key(key) {
+            let mut new_table = Table::new();
+            new_table.set_implicit(true);
+
+            table.insert(key, Item::Table(new_table));
+        }
+
+        item = table
+            .get_mut(key)
+            .context(format!("could not find {}", table_path))?;
+    }
+
+    if let Some(table) = item.as_table_mut() {
+        table.set_implicit(false);
+    }
+
+    let kept = item
+        .as_table_like_mut()
+        .context(format!("could not parse {} as a table", table_path))?
+        .entry(dependency_name)
+        .or_insert(Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .context(format!(
+            "could not parse {}.{} as an array",
+            table_path, dependency_name
+        ))?;
+
+    for feature in features {
+        if !kept
+            .iter()
+            .any(|value| value.as_str() == Some(feature.as_str()))
+        {
+            kept.push(feature.as_str());
+        }
+    }
+
+    fs::write(manifest_path, doc.to_string()).map_err(Error::from)
+}
+
+/// enables the features for the same crate in the dependency table of `kind` & `target`,
+/// creating the table and the entry if they do not exist yet
+pub fn save_dependency_features(
+    document: &Document,
+    package_name: &str,
+    dep_name: &str,
+    kind: &DependencyType,
+    target: &Option<Platform>,
+    features: &[String],
+) -> color_eyre::Result<()> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dep_name)?;
+    let key = dependency.rename.as_ref().unwrap_or(&dependency.name);
+
+    let mut doc = toml_document_from_path(&package.manifest_path)?;
+
+    let source = get_mut_item_from_doc(&get_path(&dependency.kind, &dependency.target), &mut doc)?
+        .get(key)
+        .context(format!(
+            "could not find {} in dependencies",
+            dependency.get_name()
+        ))?
+        .clone();
+
+    let path = get_path(kind, target);
+
+    let deps_table = get_or_insert_table(&path, target, &mut doc)?
+        .as_table_like_mut()
+        .context(format!("could not parse {} as a table", path))?;
+
+    if !deps_table.contains_key(key) {
+        let mut table = InlineTable::new();
+
+        match source.as_table_like() {
+            Some(source) => {
+                for (name, value) in source.iter() {
+                    if ["features", "default-features", "optional"].contains(&name) {
+                        continue;
+                    }
+
+                    if let Some(value) = value.as_value() {
+                        table.insert(name, value.clone());
+                    }
+                }
+            }
+            None => {
+                table.insert(
+                    "version",
+                    Value::String(Formatted::new(dependency.get_version())),
+                );
+            }
+        }
+
+        // the normal dependency already decides about the default features
+        if !dependency.workspace {
+            table.insert("default-features", Value::Boolean(Formatted::new(false)));
+        }
+
+        deps_table.insert(key, Item::Value(Value::InlineTable(table)));
+    }
+
+    let entry = deps_table
+        .get_mut(key)
+        .context(format!("could not find {} in {}", key, path))?;
+
+    // a plain version has to become a table to hold features
+    if let Some(version) = entry.as_str().map(|version| version.to_string()) {
+        let mut table = InlineTable::new();
+        table.insert("version", Value::String(Formatted::new(version)));
+
+        *entry = Item::Value(Value::InlineTable(table));
+    }
+
+    let table = entry
+        .as_table_like_mut()
+        .context(format!("could not parse {}.{} as a table", path, key))?;
+
+    let enabled = table
+        .entry("features")
+        .or_insert(Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .context(format!("could not parse {}.{}.features", path, key))?;
+
+    for feature in features {
+        if !enabled.iter().any(|value| value.as_str() == Some(feature)) {
+            enabled.push(feature.as_str());
+        }
+    }
+
+    fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
+}
+
+fn get_or_insert_table<'a>(
+    path: &str,
+    target: &Option<Platform>,
+    doc: &'a mut DocumentMut,
+) -> color_eyre::Result<&'a mut Item> {
+    if get_item_from_doc(path, doc).is_err() {
+        let (parent_path, name) = path.rsplit_once('.').unwrap_or(("", path));
+
+        if let Some(target) = target {
+            if get_item_from_doc(parent_path, doc).is_err() {
+                let mut target_table = Table::new();
+                target_table.set_implicit(true);
+
+                let mut targets_table = Table::new();
+                targets_table.set_implicit(true);
+
+                doc.entry("target")
+                    .or_insert(Item::Table(targets_table))
+                    .as_table_like_mut()
+                    .context("could not parse target as a table")?
+                    .insert(&target.to_string(), Item::Table(target_table));
+            }
+        }
+
+        let parent = if parent_path.is_empty() {
+            doc.as_item_mut()
+        } else {
+            get_mut_item_from_doc(parent_path, doc)?
+        };
+
+        parent
+            .as_table_like_mut()
+            .context(format!("could not parse {} as a table", parent_path))?
+            .insert(name, Item::Table(Table::new()));
+    }
+
+    get_mut_item_from_doc(path, doc)
+}
@@ -0,0 +1,141 @@
+use crate::list::table::print_table;
+use crate::project::dependency::feature::EnabledState;
+use crate::project::dependency::{Dependency, DependencyType};
+use crate::project::document::Document;
+use crate::project::package::Package;
+use crate::ListFormat;
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::Serialize;
+use std::io::Write;
+
+mod table;
+
+/// bumped whenever the json output changes in an incompatible way
+const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+pub struct ListOutput {
+    pub version: u32,
+    pub packages: Vec<PackageInfo>,
+}
+
+#[derive(Serialize)]
+pub struct PackageInfo {
+    pub name: String,
+    pub manifest_path: String,
+    pub dependencies: Vec<DependencyInfo>,
+}
+
+#[derive(Serialize)]
+pub struct DependencyInfo {
+    pub key: String,
+    pub name: String,
+    pub rename: Option<String>,
+    pub version: String,
+    pub kind: &'static str,
+    pub target: Option<String>,
+    pub workspace: bool,
+    pub default_features: bool,
+    pub features: Vec<FeatureInfo>,
+}
+
+#[derive(Serialize)]
+pub struct FeatureInfo {
+    pub name: String,
+    pub enabled: bool,
+    pub default: bool,
+    pub origin: FeatureOrigin,
+    pub sub_features: Vec<String>,
+}
+
+#[derive(Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeatureOrigin {
+    Package,
+    Workspace,
+}
+
+pub fn list(format: ListFormat, package: Option<String>) -> Result<()> {
+    let document = Document::new(".")?;
+
+    let packages = match package {
+        Some(name) => vec![document.get_package_by_key(&name)?],
+        None => document
+            .get_packages()
+            .iter()
+            .sorted_by(|package_a, package_b| package_a.name.cmp(&package_b.name))
+            .collect(),
+    };
+
+    let output = ListOutput {
+        version: SCHEMA_VERSION,
+        packages: packages
+            .into_iter()
+            .map(|package| package_info(&document, package))
+            .collect(),
+    };
+
+    match format {
+        ListFormat::Table => print_table(&output, document.is_workspace())?,
+        ListFormat::Json => {
+            let mut stdout = std::io::stdout();
+            serde_json::to_writer_pretty(&mut stdout, &output)?;
+            writeln!(stdout)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn package_info(document: &Document, package: &Package) -> PackageInfo {
+    PackageInfo {
+        name: document.get_package_key(&package.name),
+        manifest_path: package.manifest_path.to_string(),
+        dependencies: package
+            .get_deps()
+            .iter()
+            .sorted_by_key(|dependency| dependency.get_key())
+            .map(dependency_info)
+            .collect(),
+    }
+}
+
+fn dependency_info(dependency: &Dependency) -> DependencyInfo {
+    DependencyInfo {
+        key: dependency.get_key(),
+        name: dependency.name.to_string(),
+        rename: dependency.rename.clone(),
+        version: dependency.get_version(),
+        kind: match dependency.kind {
+            DependencyType::Normal => "normal",
+            DependencyType::Development => "development",
+            DependencyType::Build => "build",
+            DependencyType::Workspace => "workspace",
+            DependencyType::Unknown => "unknown",
+        },
+        target: dependency.target.as_ref().map(|target| target.to_string()),
+        workspace: dependency.workspace,
+        default_features: dependency.uses_default_features(),
+        features: dependency
+            .features
+            .iter()
+            .filter(|(name, _)| *name != "default")
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            .map(|(name, data)| FeatureInfo {
+                name: name.to_string(),
+                enabled: data.is_enabled(),
+                default: data.is_default,
+                origin: match data.enabled_state {
+                    EnabledState::Normal(_) => FeatureOrigin::Package,
+                    EnabledState::Workspace => FeatureOrigin::Workspace,
+                },
+                sub_features: data
+                    .sub_features
+                    .iter()
+                    .map(|sub_feature| sub_feature.name.to_string())
+                    .collect(),
+            })
+            .collect(),
+    }
+}
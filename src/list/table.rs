@@ -0,0 +1,72 @@
+use crate::list::{FeatureOrigin, ListOutput};
+use color_eyre::Result;
+use console::{style, Emoji, Term};
+use itertools::Itertools;
+use std::io::Write;
+
+pub fn print_table(output: &ListOutput, is_workspace: bool) -> Result<()> {
+    let term = Term::stdout();
+
+    let dependency_inset = if is_workspace { 2 } else { 0 };
+    let feature_inset = dependency_inset + 2;
+
+    for package in &output.packages {
+        if is_workspace {
+            writeln!(&term, "{}", style(&package.name).bold())?;
+        }
+
+        for dependency in &package.dependencies {
+            write!(
+                &term,
+                "{:dependency_inset$}{} {}",
+                "", dependency.key, dependency.version
+            )?;
+
+            if let Some(rename) = &dependency.rename {
+                write!(&term, "{}", style(format!(" ({})", rename)).color256(8))?;
+            }
+
+            writeln!(&term)?;
+
+            let name_width = dependency
+                .features
+                .iter()
+                .map(|feature| feature.name.len())
+                .max()
+                .unwrap_or_default();
+
+            for feature in &dependency.features {
+                let marker = if feature.origin == FeatureOrigin::Workspace {
+                    format!("{}", Emoji("🗃️", "W"))
+                } else if feature.enabled {
+                    "[X]".to_string()
+                } else {
+                    "[ ]".to_string()
+                };
+
+                let marker = if feature.default {
+                    style(marker).green()
+                } else {
+                    style(marker)
+                };
+
+                write!(&term, "{:feature_inset$}{} ", "", marker)?;
+
+                if feature.sub_features.is_empty() {
+                    write!(&term, "{}", feature.name)?;
+                } else {
+                    write!(
+                        &term,
+                        "{:name_width$} {}",
+                        feature.name,
+                        style(format!("└ {}", feature.sub_features.iter().join(" "))).color256(8)
+                    )?;
+                }
+
+                writeln!(&term)?;
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -1,19 +1,26 @@
 #![warn(clippy::unwrap_used)]
 
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::process::exit;
 
-use clap::{arg, CommandFactory, Parser, Subcommand};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use clap_complete::{generate, Shell};
 use color_eyre::Result;
 use console::Term;
+use serde::{Deserialize, Serialize};
 
 use crate::edit::display::Display;
-use crate::prune::prune;
+use crate::list::list;
+use crate::prune::{interrupt, prune};
+use crate::toggle::{disable, enable};
 
 mod edit;
+mod list;
 mod prune;
 
 mod project;
+mod toggle;
 
 mod io;
 
@@ -39,14 +46,144 @@ struct FeaturesArgs {
 
 #[derive(Subcommand)]
 enum FeaturesSubCommands {
-    Prune {
-        #[arg(long, short)]
-        dry_run: bool,
-        #[arg(long, short)]
-        skip_tests: bool,
-        /// `cargo clean` will run after each <CLEAN>
+    /// print the features of all dependencies without opening the tui
+    List {
         #[arg(long, short, default_value_t, value_enum)]
-        clean: CleanLevel,
+        format: ListFormat,
+        /// only list the dependencies of <PACKAGE>
+        #[arg(long, short)]
+        package: Option<String>,
+    },
+    /// enable features - same as selecting them in the tui
+    Enable {
+        /// <package>/<dependency>/<feature> - the package can be omitted outside of workspaces
+        #[arg(required = true)]
+        features: Vec<String>,
+    },
+    /// disable features - features which require them are disabled as well
+    Disable {
+        /// <package>/<dependency>/<feature> - the package can be omitted outside of workspaces
+        #[arg(required = true)]
+        features: Vec<String>,
+    },
+    Prune(Box<PruneArgs>),
+}
+
+#[derive(clap::Args)]
+struct PruneArgs {
+    #[command(subcommand)]
+    command: Option<PruneCommand>,
+    #[arg(long, short)]
+    dry_run: bool,
+    /// do not change anything but fail if any feature could be disabled
+    #[arg(long, conflicts_with = "dry_run")]
+    check: bool,
+    /// review every proposed change before it is applied - accept, reject or keep it permanently
+    #[arg(long, short, conflicts_with_all = ["dry_run", "check", "move_to_dev", "move_to_target"])]
+    interactive: bool,
+    #[arg(long, short)]
+    skip_tests: bool,
+    /// `cargo clean` will run after each <CLEAN>
+    #[arg(long, short, default_value_t, value_enum)]
+    clean: CleanLevel,
+    /// command used instead of `cargo build --workspace` followed by `cargo build --workspace --all-targets`
+    #[arg(long, value_name = "COMMAND")]
+    build_command: Option<String>,
+    /// command used instead of `cargo test --workspace`
+    #[arg(long, value_name = "COMMAND")]
+    test_command: Option<String>,
+    /// check every <TARGET> and find features which are only needed on some of them
+    #[arg(long, value_name = "TARGET")]
+    target: Vec<String>,
+    /// move features only needed on some targets to `[target.<TARGET>.dependencies]`
+    #[arg(long)]
+    move_to_target: bool,
+    /// kill the build after <SECONDS> and mark the feature as inconclusive
+    #[arg(long, value_name = "SECONDS")]
+    build_timeout: Option<u64>,
+    /// kill the tests after <SECONDS> and mark the feature as inconclusive
+    #[arg(long, value_name = "SECONDS")]
+    test_timeout: Option<u64>,
+    /// run cargo as `cargo +<TOOLCHAIN>`
+    #[arg(long, value_name = "TOOLCHAIN")]
+    toolchain: Option<String>,
+    /// run cargo without accessing the network
+    #[arg(long)]
+    offline: bool,
+    /// run cargo with `--locked`
+    #[arg(long)]
+    locked: bool,
+    /// run cargo with `--frozen`
+    #[arg(long)]
+    frozen: bool,
+    /// build and test with <PROFILE>
+    #[arg(long, value_name = "PROFILE")]
+    profile: Option<String>,
+    /// run failed tests up to <RETRIES> more times before a feature is kept, for flaky tests
+    #[arg(long, value_name = "RETRIES")]
+    retries: Option<u64>,
+    /// features of your own crates which are enabled while checking
+    #[arg(long, short = 'F', value_delimiter = ',')]
+    features: Vec<String>,
+    /// enable all features of your own crates while checking
+    #[arg(long, conflicts_with = "features")]
+    all_features: bool,
+    /// check every combination of features of your own crates - `none`, `default`, `all` or a comma separated
+    /// list of features, a dependency feature is only disabled if all of them pass - can be repeated
+    #[arg(long, value_name = "FEATURES", conflicts_with_all = ["features", "all_features"])]
+    feature_matrix: Vec<String>,
+    /// only prune packages matching <PACKAGE>, supports globs - use `workspace` for `[workspace.dependencies]`
+    #[arg(long, short, value_name = "PACKAGE")]
+    package: Vec<String>,
+    /// only prune dependencies matching <DEPENDENCY>, supports globs
+    #[arg(long, value_name = "DEPENDENCY")]
+    dependency: Vec<String>,
+    /// skip dependencies matching <DEPENDENCY>, supports globs
+    #[arg(long, value_name = "DEPENDENCY")]
+    exclude: Vec<String>,
+    /// try `default-features = false` before testing the default features one by one
+    #[arg(long)]
+    try_no_default: bool,
+    /// `bisect` disables multiple features at once and only splits them up if the check fails,
+    /// `minimize` disables all features and enables the ones the compiler errors point to
+    #[arg(long, default_value_t, value_enum)]
+    strategy: PruneStrategy,
+    /// continue an interrupted prune, results are kept as long as no manifest or the lock file changed
+    #[arg(long, short)]
+    resume: bool,
+    /// only test the <K>-th of <N> slices of the dependencies, e.g. one per CI runner
+    #[arg(long, value_name = "K/N", value_parser = parse_shard)]
+    shard: Option<Shard>,
+    /// test <JOBS> dependencies at once, each in its own copy of the project
+    #[arg(long, short, default_value = "1")]
+    jobs: NonZeroUsize,
+    /// move features which are only needed by tests to `[dev-dependencies]`
+    #[arg(long)]
+    move_to_dev: bool,
+    /// write the result as <FORMAT>=<PATH>, formats are json, markdown and junit - can be repeated
+    #[arg(long, value_name = "FORMAT=PATH", value_parser = parse_report)]
+    report: Vec<(ReportFormat, PathBuf)>,
+    /// show the errors which prevented kept features from being disabled
+    #[arg(long, short)]
+    explain: bool,
+    /// write the changes to <FILE> as a unified diff instead of applying them
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["dry_run", "check"])]
+    emit_patch: Option<PathBuf>,
+    /// arguments added to every cargo build, test & check
+    #[arg(last = true, value_name = "CARGO_ARGS")]
+    cargo_args: Vec<String>,
+    /// `json` prints one event per line instead of the progress display
+    #[arg(long, default_value_t, value_enum)]
+    message_format: MessageFormat,
+}
+
+#[derive(Subcommand)]
+enum PruneCommand {
+    /// combine the json reports of `--shard` runs and continue as if they were a single run - options of prune go before `merge`
+    Merge {
+        /// reports written with `--report json=<PATH>`
+        #[arg(required = true, value_name = "REPORT")]
+        reports: Vec<PathBuf>,
     },
 }
 
@@ -58,6 +195,81 @@ enum CleanLevel {
     Dependency,
 }
 
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+enum PruneStrategy {
+    #[default]
+    Linear,
+    Bisect,
+    Minimize,
+}
+
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+enum MessageFormat {
+    #[default]
+    Human,
+    Json,
+}
+
+#[derive(clap::ValueEnum, Clone, Debug)]
+enum ReportFormat {
+    Json,
+    Markdown,
+    Junit,
+}
+
+/// the <index>-th of <count> slices, starting at 1
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+struct Shard {
+    index: usize,
+    count: usize,
+}
+
+impl std::fmt::Display for Shard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.index, self.count)
+    }
+}
+
+fn parse_shard(value: &str) -> std::result::Result<Shard, String> {
+    let (index, count) = value
+        .split_once('/')
+        .ok_or("expected <K>/<N>".to_string())?;
+
+    let shard = Shard {
+        index: index
+            .parse()
+            .map_err(|_| format!("invalid shard {}", index))?,
+        count: count
+            .parse()
+            .map_err(|_| format!("invalid shard count {}", count))?,
+    };
+
+    if shard.count == 0 {
+        return Err("shard count has to be at least 1".to_string());
+    }
+
+    if shard.index == 0 || shard.index > shard.count {
+        return Err(format!("shard has to be between 1 and {}", shard.count));
+    }
+
+    Ok(shard)
+}
+
+fn parse_report(value: &str) -> std::result::Result<(ReportFormat, PathBuf), String> {
+    let (format, path) = value
+        .split_once('=')
+        .ok_or("expected <FORMAT>=<PATH>".to_string())?;
+
+    Ok((ReportFormat::from_str(format, true)?, PathBuf::from(path)))
+}
+
+#[derive(clap::ValueEnum, Clone, Default, Debug)]
+enum ListFormat {
+    #[default]
+    Table,
+    Json,
+}
+
 fn main() -> Result<()> {
     color_eyre::install()?;
 
@@ -80,20 +292,28 @@ fn main() -> Result<()> {
 
 fn run(args: FeaturesArgs) -> Result<()> {
     let _ = ctrlc::set_handler(|| {
+        let was_pruning = interrupt();
+
         let term = Term::stdout();
         term.show_cursor().expect("could not enable cursor");
 
-        exit(0);
+        // an interrupted prune did not finish, so `--check` must not pass
+        exit(if was_pruning { 130 } else { 0 });
     });
 
     if let Some(sub) = args.sub {
         match sub {
-            FeaturesSubCommands::Prune {
-                dry_run,
-                skip_tests,
-                clean,
-            } => {
-                prune(dry_run, skip_tests, clean)?;
+            FeaturesSubCommands::List { format, package } => {
+                list(format, package)?;
+            }
+            FeaturesSubCommands::Enable { features } => {
+                enable(features)?;
+            }
+            FeaturesSubCommands::Disable { features } => {
+                disable(features)?;
+            }
+            FeaturesSubCommands::Prune(args) => {
+                prune(*args)?;
             }
         }
     } else {
@@ -108,3 +328,33 @@ fn run(args: FeaturesArgs) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::parse_shard;
+
+    #[test]
+    fn parse_shard_accepts_valid_shards() -> Result<(), String> {
+        let shard = parse_shard("2/3")?;
+
+        assert_eq!((shard.index, shard.count), (2, 3));
+        assert!(parse_shard("1/1").is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn parse_shard_rejects_out_of_range_shards() {
+        assert!(parse_shard("0/2").is_err());
+        assert!(parse_shard("3/2").is_err());
+        assert!(parse_shard("1/0").is_err());
+    }
+
+    #[test]
+    fn parse_shard_rejects_malformed_shards() {
+        assert!(parse_shard("2").is_err());
+        assert!(parse_shard("a/2").is_err());
+        assert!(parse_shard("1/b").is_err());
+        assert!(parse_shard("-1/2").is_err());
+    }
+}
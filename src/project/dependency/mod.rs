@@ -62,6 +62,32 @@ impl Dependency {
         name
     }
 
+    /// plain identifier used to reference the dependency from the command line
+    pub fn get_key(&self) -> String {
+        let mut key = if let Some(target) = &self.target {
+            format!("{}.{}", target, self.name)
+        } else {
+            self.name.to_string()
+        };
+
+        match self.kind {
+            DependencyType::Normal | DependencyType::Workspace => {}
+            DependencyType::Development => key = format!("dev:{}", key),
+            DependencyType::Build => key = format!("build:{}", key),
+            DependencyType::Unknown => key = format!("unknown:{}", key),
+        }
+
+        key
+    }
+
+    /// every default feature is enabled - also true for workspace dependencies, which inherit `default-features`
+    pub fn uses_default_features(&self) -> bool {
+        self.features
+            .values()
+            .filter(|data| data.is_default)
+            .all(|data| data.is_enabled())
+    }
+
     pub fn get_version(&self) -> String {
         self.version.to_string()
     }
@@ -74,18 +100,9 @@ impl Dependency {
         !self.features.is_empty()
     }
 
+    /// the default features can be used as is - always false for `workspace = true`, which can not set `default-features` itself
     pub fn can_use_default(&self) -> bool {
-        if self.workspace {
-            return false;
-        }
-
-        for data in self.features.values() {
-            if data.is_default && !data.is_enabled() {
-                return false;
-            }
-        }
-
-        true
+        !self.workspace && self.uses_default_features()
     }
 
     pub fn get_features_to_enable(&self) -> Vec<String> {
@@ -182,7 +199,7 @@ impl Dependency {
     }
 
     /// returns all features which require the feature to be enabled
-    fn get_dependent_features(&self, feature_name: &str) -> Vec<String> {
+    pub fn get_dependent_features(&self, feature_name: &str) -> Vec<String> {
         let mut dep_features = vec![];
 
         for (name, data) in &self.features {
@@ -8,6 +8,8 @@ use crate::io::parsing::package::get_packages;
 use crate::project::dependency::feature::EnabledState;
 use crate::project::package::Package;
 
+pub const WORKSPACE_KEY: &str = "workspace";
+
 pub struct Document {
     packages: Vec<Package>,
     workspace_index: Option<usize>,
@@ -16,7 +18,16 @@ pub struct Document {
 
 impl Document {
     pub fn new(path: impl Into<PathBuf>) -> Result<Document> {
-        let (mut packages, workspace, root_path) = get_packages(path)?;
+        Self::with_cargo_options(path, None, &[])
+    }
+
+    /// loads the document with `cargo metadata` run on `toolchain` and with `options` like `--offline`
+    pub fn with_cargo_options(
+        path: impl Into<PathBuf>,
+        toolchain: Option<&str>,
+        options: &[String],
+    ) -> Result<Document> {
+        let (mut packages, workspace, root_path) = get_packages(path, toolchain, options)?;
 
         if packages.len() == 1
             && packages
@@ -138,6 +149,24 @@ impl Document {
             .context(format!("no package with name {} found", package))
     }
 
+    /// finds a package by its name, `workspace` refers to the `[workspace.dependencies]`
+    pub fn get_package_by_key(&self, key: &str) -> Result<&Package> {
+        if key == WORKSPACE_KEY {
+            if let Some(workspace_index) = self.workspace_index {
+                return self.get_package_by_id(workspace_index);
+            }
+        }
+
+        self.get_package(key)
+    }
+
+    pub fn get_package_key(&self, package_name: &str) -> String {
+        match self.workspace_index {
+            Some(index) if self.packages[index].name == package_name => WORKSPACE_KEY.to_string(),
+            _ => package_name.to_string(),
+        }
+    }
+
     pub fn workspace_index(&self) -> Option<usize> {
         self.workspace_index
     }
@@ -22,6 +22,34 @@ impl Package {
         }
     }
 
+    /// finds a dependency by its key or, if unambiguous, by its plain name
+    pub fn get_dep_by_key(&self, key: &str) -> color_eyre::Result<&Dependency> {
+        if let Some(dep) = self.dependencies.iter().find(|dep| dep.get_key() == key) {
+            return Ok(dep);
+        }
+
+        let mut deps = self
+            .dependencies
+            .iter()
+            .filter(|dep| dep.name == key || dep.rename.as_ref().is_some_and(|name| name == key));
+
+        match (deps.next(), deps.next()) {
+            (None, _) => bail!(
+                "could not find dependency with name {} in {}",
+                key,
+                self.name
+            ),
+            (Some(dep), None) => Ok(dep),
+            (Some(_), Some(_)) => bail!(
+                "{} is ambiguous in {} - use e.g. dev:{} or build:{}",
+                key,
+                self.name,
+                key,
+                key
+            ),
+        }
+    }
+
     pub fn get_dep_index(&self, name: &String) -> color_eyre::Result<usize> {
         Ok(self
             .dependencies
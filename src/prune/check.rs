@@ -0,0 +1,1102 @@
+use crate::project::document::Document;
+use crate::prune::config::PruneConfig;
+use crate::prune::copy::get_target_dir;
+use crate::prune::FeatureName;
+use crate::PruneArgs;
+use cargo_metadata::diagnostic::{Diagnostic, DiagnosticLevel};
+use cargo_metadata::Message;
+use color_eyre::eyre::{bail, eyre, ContextCompat};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// how many error messages are kept to explain why a feature is needed
+const MAX_MESSAGES: usize = 5;
+
+/// ids of the running commands - each leads its own process group, which ctrl-c does not reach
+static RUNNING: Mutex<Vec<u32>> = Mutex::new(vec![]);
+
+/// in the order they run, an earlier step outweighs a later one
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStep {
+    Build,
+    /// compiling tests, examples & benches - only with the default build command
+    TestBuild,
+    Test,
+}
+
+impl CheckStep {
+    /// the build of the library & binaries passed, so the feature is only needed by tests
+    pub fn is_test_only(&self) -> bool {
+        matches!(self, CheckStep::TestBuild | CheckStep::Test)
+    }
+}
+
+impl std::fmt::Display for CheckStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CheckStep::Build => f.write_str("build"),
+            CheckStep::TestBuild => f.write_str("test build"),
+            CheckStep::Test => f.write_str("test"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Failure {
+    pub step: CheckStep,
+    /// the first errors of the failing command
+    pub messages: Vec<String>,
+    /// the targets the check failed for, empty if no targets were given
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// the combinations of the feature matrix the check failed for, empty without a matrix
+    #[serde(default)]
+    pub combinations: Vec<String>,
+}
+
+pub enum CheckResult {
+    Passed,
+    Failed(Failure),
+    /// the step did not finish in time, so it is unknown if the feature is needed
+    TimedOut,
+}
+
+impl CheckResult {
+    pub fn is_passed(&self) -> bool {
+        matches!(self, CheckResult::Passed)
+    }
+}
+
+pub enum CompileResult {
+    Passed,
+    /// every compiler error together with the features the items it refers to are gated behind
+    Failed(Vec<(Vec<FeatureName>, String)>),
+    TimedOut,
+}
+
+/// options of prune which are passed to every cargo invocation
+#[derive(Debug, Default)]
+pub struct CargoOptions {
+    /// used as `cargo +<toolchain>`
+    toolchain: Option<String>,
+    /// `--offline`, `--locked` & `--frozen` - accepted by every cargo command
+    flags: Vec<String>,
+    profile: Option<String>,
+    /// everything after `--`, only passed to build, test & check
+    args: Vec<String>,
+}
+
+impl CargoOptions {
+    pub fn new(args: &PruneArgs) -> CargoOptions {
+        let flags = [
+            (args.offline, "--offline"),
+            (args.locked, "--locked"),
+            (args.frozen, "--frozen"),
+        ]
+        .into_iter()
+        .filter(|(is_set, _)| *is_set)
+        .map(|(_, flag)| flag.to_string())
+        .collect();
+
+        CargoOptions {
+            toolchain: args.toolchain.clone(),
+            flags,
+            profile: args.profile.clone(),
+            args: args.cargo_args.clone(),
+        }
+    }
+
+    /// `cargo metadata` only gets the toolchain and the flags
+    pub fn load_document(&self, path: impl Into<PathBuf>) -> Result<Document> {
+        Document::with_cargo_options(path, self.toolchain.as_deref(), &self.flags)
+    }
+
+    /// adds the options if the command runs cargo - before a `--`, as everything after it belongs to another program
+    fn apply(&self, mut command: Vec<String>, with_args: bool) -> Vec<String> {
+        let is_cargo = command.first().is_some_and(|program| {
+            Path::new(program)
+                .file_stem()
+                .is_some_and(|name| name == "cargo")
+        });
+
+        if !is_cargo {
+            return command;
+        }
+
+        let mut options = self.flags.clone();
+
+        let subcommand = command
+            .iter()
+            .skip(1)
+            .find(|arg| !arg.starts_with(['+', '-']));
+
+        if let Some(profile) = &self.profile {
+            // nextest has profiles of its own
+            let option = match subcommand.map(String::as_str) {
+                Some("build" | "check" | "test" | "clippy" | "bench" | "run" | "rustc" | "doc") => {
+                    Some("--profile")
+                }
+                Some("nextest") => Some("--cargo-profile"),
+                _ => None,
+            };
+
+            if let Some(option) = option {
+                options.push(option.to_string());
+                options.push(profile.to_string());
+            }
+        }
+
+        if with_args {
+            options.extend(self.args.iter().cloned());
+        }
+
+        let index = command
+            .iter()
+            .position(|arg| arg == "--")
+            .unwrap_or(command.len());
+
+        command.splice(index..index, options);
+
+        // a toolchain given in the command itself wins
+        let has_toolchain = command.get(1).is_some_and(|arg| arg.starts_with('+'));
+
+        if let (Some(toolchain), false) = (&self.toolchain, has_toolchain) {
+            command.insert(1, format!("+{}", toolchain));
+        }
+
+        command
+    }
+
+    /// scripts calling cargo pick up the toolchain and offline mode from the environment
+    fn get_env(&self) -> Vec<(String, String)> {
+        let mut env = vec![];
+
+        if let Some(toolchain) = &self.toolchain {
+            env.push(("RUSTUP_TOOLCHAIN".to_string(), toolchain.to_string()));
+        }
+
+        if self
+            .flags
+            .iter()
+            .any(|flag| flag == "--offline" || flag == "--frozen")
+        {
+            env.push(("CARGO_NET_OFFLINE".to_string(), "true".to_string()));
+        }
+
+        env
+    }
+}
+
+/// the commands for one set of features of your own crates
+#[derive(Debug)]
+struct Combination {
+    /// as given in the feature matrix, `None` without a matrix
+    name: Option<String>,
+    build: Vec<String>,
+    /// `None` for a custom build command
+    test_build: Option<Vec<String>>,
+    /// `cargo check` with json messages to find the features the compiler is missing
+    compile: Vec<String>,
+    test: Option<Vec<String>>,
+}
+
+/// decides if the project still works with the currently enabled features
+#[derive(Debug)]
+pub struct Checker {
+    /// a feature can only be disabled if every combination passes
+    combinations: Vec<Combination>,
+    build_timeout: Option<Duration>,
+    test_timeout: Option<Duration>,
+    /// failed tests are run again up to this many times
+    retries: u64,
+    targets: Vec<String>,
+    /// tests can only run for the target of the host
+    host: Option<String>,
+    clean: Vec<String>,
+    env: Vec<(String, String)>,
+}
+
+impl Checker {
+    pub fn new(args: &PruneArgs, config: &PruneConfig) -> Result<Checker> {
+        let features = if args.features.is_empty() {
+            &config.features
+        } else {
+            &args.features
+        };
+
+        let cargo_options = CargoOptions::new(args);
+        let env = cargo_options.get_env();
+
+        let build_command = args.build_command.as_ref().or(config.build.as_ref());
+        let test_command = args.test_command.as_ref().or(config.test.as_ref());
+
+        // features given on the command line replace the matrix of the config
+        let matrix = if !args.feature_matrix.is_empty() {
+            args.feature_matrix.as_slice()
+        } else if args.all_features || !args.features.is_empty() {
+            &[]
+        } else {
+            config.feature_matrix.as_slice()
+        };
+
+        let mut combinations = vec![];
+
+        if matrix.is_empty() {
+            let mut feature_args = vec![];
+
+            if args.all_features || config.all_features {
+                feature_args.push("--all-features".to_string());
+            } else if !features.is_empty() {
+                feature_args.push("--features".to_string());
+                feature_args.push(features.join(","));
+            }
+
+            combinations.push((None, feature_args));
+        } else {
+            if build_command.is_some() || test_command.is_some() {
+                bail!("the feature matrix can only be used with the default build & test commands");
+            }
+
+            for combination in matrix {
+                combinations.push((
+                    Some(combination.to_string()),
+                    parse_combination(combination)?,
+                ));
+            }
+        }
+
+        let combinations = combinations
+            .into_iter()
+            .map(|(name, feature_args)| {
+                let build = match build_command {
+                    Some(command) => parse_command(command)?,
+                    None => ["cargo", "build", "--workspace"]
+                        .iter()
+                        .map(|arg| arg.to_string())
+                        .chain(feature_args.iter().cloned())
+                        .collect(),
+                };
+
+                // a separate step, so features only test code needs are not reported as needed by the build
+                let test_build: Option<Vec<String>> = match build_command {
+                    Some(_) => None,
+                    None => Some(
+                        ["cargo", "build", "--workspace", "--all-targets"]
+                            .iter()
+                            .map(|arg| arg.to_string())
+                            .chain(feature_args.iter().cloned())
+                            .collect(),
+                    ),
+                };
+
+                let test = match test_command {
+                    Some(command) => parse_command(command)?,
+                    None => ["cargo", "test", "--workspace"]
+                        .iter()
+                        .map(|arg| arg.to_string())
+                        .chain(feature_args.iter().cloned())
+                        .collect(),
+                };
+
+                let compile: Vec<String> = [
+                    "cargo",
+                    "check",
+                    "--workspace",
+                    "--all-targets",
+                    "--message-format=json",
+                ]
+                .iter()
+                .map(|arg| arg.to_string())
+                .chain(feature_args.iter().cloned())
+                .collect();
+
+                Ok(Combination {
+                    name,
+                    build: cargo_options.apply(build, true),
+                    test_build: test_build.map(|command| cargo_options.apply(command, true)),
+                    compile: cargo_options.apply(compile, true),
+                    test: if args.skip_tests {
+                        None
+                    } else {
+                        Some(cargo_options.apply(test, true))
+                    },
+                })
+            })
+            .collect::<Result<Vec<Combination>>>()?;
+
+        let clean = vec!["cargo".to_string(), "clean".to_string()];
+
+        Ok(Checker {
+            combinations,
+            build_timeout: args
+                .build_timeout
+                .or(config.build_timeout)
+                .map(Duration::from_secs),
+            test_timeout: args
+                .test_timeout
+                .or(config.test_timeout)
+                .map(Duration::from_secs),
+            retries: args.retries.or(config.retries).unwrap_or(0),
+            targets: args.target.clone(),
+            host: if args.target.is_empty() {
+                None
+            } else {
+                Some(get_host(&env)?)
+            },
+            clean: cargo_options.apply(clean, false),
+            env,
+        })
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    pub fn clean<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        execute(&self.clean, path, None, &self.env, None)?;
+
+        Ok(())
+    }
+
+    /// makes sure the unmodified project passes - otherwise every feature would look required
+    pub fn check_baseline<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        match self.check(path)? {
+            CheckResult::Passed => Ok(()),
+            CheckResult::TimedOut => bail!(
+                "the unmodified project does not finish its checks in time - increase the timeouts first"
+            ),
+            CheckResult::Failed(failure) => {
+                let mut targets = if failure.targets.is_empty() {
+                    String::new()
+                } else {
+                    format!(" for {}", failure.targets.join(", "))
+                };
+
+                if !failure.combinations.is_empty() {
+                    targets.push_str(&format!(
+                        " with the features {}",
+                        failure.combinations.join(", ")
+                    ));
+                }
+
+                let mut message = format!(
+                    "the {} step already fails for the unmodified project{} - fix it or adjust the check commands first",
+                    failure.step, targets
+                );
+
+                for line in &failure.messages {
+                    message.push('\n');
+                    message.push_str(line);
+                }
+
+                bail!(message)
+            }
+        }
+    }
+
+    /// compiles every combination for the host and collects the errors, the build & test commands are not used
+    pub fn check_compile<P: AsRef<Path>>(&self, path: P) -> Result<CompileResult> {
+        let mut errors = vec![];
+
+        for combination in &self.combinations {
+            match self.check_compile_combination(&path, combination)? {
+                CompileResult::Passed => {}
+                CompileResult::TimedOut => return Ok(CompileResult::TimedOut),
+                CompileResult::Failed(combination_errors) => {
+                    for error in combination_errors {
+                        if !errors.contains(&error) {
+                            errors.push(error);
+                        }
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            return Ok(CompileResult::Passed);
+        }
+
+        Ok(CompileResult::Failed(errors))
+    }
+
+    fn check_compile_combination<P: AsRef<Path>>(
+        &self,
+        path: P,
+        combination: &Combination,
+    ) -> Result<CompileResult> {
+        let Some(output) = execute(
+            &combination.compile,
+            path,
+            None,
+            &self.env,
+            self.build_timeout,
+        )?
+        else {
+            return Ok(CompileResult::TimedOut);
+        };
+
+        if output.is_success {
+            return Ok(CompileResult::Passed);
+        }
+
+        let errors = Message::parse_stream(output.stdout.as_bytes())
+            .filter_map(|message| match message {
+                Ok(Message::CompilerMessage(message))
+                    if message.message.level == DiagnosticLevel::Error =>
+                {
+                    Some(message.message)
+                }
+                _ => None,
+            })
+            .map(|diagnostic| (get_gating_features(&diagnostic), describe(&diagnostic)))
+            .collect_vec();
+
+        if errors.is_empty() {
+            return Ok(CompileResult::Failed(vec![(
+                vec![],
+                summarize_output(&output.stderr).join("\n"),
+            )]));
+        }
+
+        Ok(CompileResult::Failed(errors))
+    }
+
+    /// checks every combination of the feature matrix, a failure lists all combinations which failed
+    pub fn check<P: AsRef<Path>>(&self, path: P) -> Result<CheckResult> {
+        let mut failure: Option<Failure> = None;
+
+        for combination in &self.combinations {
+            match self.check_combination(&path, combination)? {
+                CheckResult::Passed => {}
+                CheckResult::TimedOut => return Ok(CheckResult::TimedOut),
+                CheckResult::Failed(combination_failure) => {
+                    let combination_failure = Failure {
+                        combinations: combination.name.iter().cloned().collect(),
+                        ..combination_failure
+                    };
+
+                    let merged = match failure.take() {
+                        Some(failure) => merge_failures(failure, combination_failure),
+                        None => combination_failure,
+                    };
+
+                    let failure = failure.insert(merged);
+
+                    // the remaining combinations can not change the outcome anymore,
+                    // test failures are checked further as the build might still fail
+                    if failure.step == CheckStep::Build
+                        && failure.targets.len() == self.targets.len()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+
+        Ok(failure.map_or(CheckResult::Passed, CheckResult::Failed))
+    }
+
+    /// checks every target, a failure lists all targets which failed
+    fn check_combination<P: AsRef<Path>>(
+        &self,
+        path: P,
+        combination: &Combination,
+    ) -> Result<CheckResult> {
+        if self.targets.is_empty() {
+            return self.check_target(&path, combination, None);
+        }
+
+        let mut failure: Option<Failure> = None;
+
+        for target in &self.targets {
+            match self.check_target(&path, combination, Some(target))? {
+                CheckResult::Passed => {}
+                CheckResult::TimedOut => return Ok(CheckResult::TimedOut),
+                CheckResult::Failed(target_failure) => match &mut failure {
+                    Some(failure) => failure.targets.push(target.to_string()),
+                    None => {
+                        failure = Some(Failure {
+                            targets: vec![target.to_string()],
+                            ..target_failure
+                        })
+                    }
+                },
+            }
+        }
+
+        let is_host_missing = self
+            .host
+            .as_ref()
+            .is_some_and(|host| !self.targets.contains(host));
+
+        let has_failed_everywhere = failure
+            .as_ref()
+            .is_some_and(|failure| failure.targets.len() == self.targets.len());
+
+        // tests can only run on the host, so they run there once instead of being skipped
+        if is_host_missing && !has_failed_everywhere {
+            match self.test(&path, combination, None)? {
+                CheckResult::Passed => {}
+                CheckResult::TimedOut => return Ok(CheckResult::TimedOut),
+                // the tests need the feature on the host, so it is not only needed for the failed targets
+                CheckResult::Failed(test_failure) => {
+                    failure = Some(match failure {
+                        Some(failure) => Failure {
+                            targets: vec![],
+                            ..failure
+                        },
+                        None => test_failure,
+                    })
+                }
+            }
+        }
+
+        Ok(failure.map_or(CheckResult::Passed, CheckResult::Failed))
+    }
+
+    fn check_target<P: AsRef<Path>>(
+        &self,
+        path: P,
+        combination: &Combination,
+        target: Option<&str>,
+    ) -> Result<CheckResult> {
+        let result = run(
+            &combination.build,
+            &path,
+            target,
+            &self.env,
+            self.build_timeout,
+            CheckStep::Build,
+        )?;
+
+        if !result.is_passed() {
+            return Ok(result);
+        }
+
+        if let Some(test_build) = &combination.test_build {
+            let result = run(
+                test_build,
+                &path,
+                target,
+                &self.env,
+                self.build_timeout,
+                CheckStep::TestBuild,
+            )?;
+
+            if !result.is_passed() {
+                return Ok(result);
+            }
+        }
+
+        let can_run_tests = match target {
+            Some(target) => self.host.as_deref() == Some(target),
+            None => true,
+        };
+
+        if !can_run_tests {
+            return Ok(CheckResult::Passed);
+        }
+
+        self.test(path, combination, target)
+    }
+
+    fn test<P: AsRef<Path>>(
+        &self,
+        path: P,
+        combination: &Combination,
+        target: Option<&str>,
+    ) -> Result<CheckResult> {
+        if let Some(test) = &combination.test {
+            let mut result = run(
+                test,
+                &path,
+                target,
+                &self.env,
+                self.test_timeout,
+                CheckStep::Test,
+            )?;
+
+            // a flaky test should not keep a feature, so a single passing run is enough
+            for _ in 0..self.retries {
+                if !matches!(result, CheckResult::Failed(_)) {
+                    break;
+                }
+
+                result = run(
+                    test,
+                    &path,
+                    target,
+                    &self.env,
+                    self.test_timeout,
+                    CheckStep::Test,
+                )?;
+            }
+
+            return Ok(result);
+        }
+
+        Ok(CheckResult::Passed)
+    }
+}
+
+/// the earlier step outweighs the later one, as features are only moved to `[dev-dependencies]` if every build passes
+fn merge_failures(failure: Failure, other: Failure) -> Failure {
+    if failure.step != other.step {
+        return if failure.step < other.step {
+            failure
+        } else {
+            other
+        };
+    }
+
+    let mut targets = failure.targets;
+
+    for target in other.targets {
+        if !targets.contains(&target) {
+            targets.push(target);
+        }
+    }
+
+    Failure {
+        targets,
+        combinations: failure
+            .combinations
+            .into_iter()
+            .chain(other.combinations)
+            .collect(),
+        ..failure
+    }
+}
+
+/// `default`, `all` or a comma separated list of features - `none` in the list disables the default features
+fn parse_combination(combination: &str) -> Result<Vec<String>> {
+    match combination.trim() {
+        "default" => return Ok(vec![]),
+        "all" => return Ok(vec!["--all-features".to_string()]),
+        _ => {}
+    }
+
+    let mut args = vec![];
+    let mut features = vec![];
+
+    for feature in combination.split(',').map(str::trim) {
+        match feature {
+            "" => bail!(
+                "invalid feature combination {:?} - empty feature",
+                combination
+            ),
+            "none" => args.push("--no-default-features".to_string()),
+            "default" | "all" => bail!(
+                "invalid feature combination {:?} - {} can not be combined with other features",
+                combination,
+                feature
+            ),
+            feature => features.push(feature),
+        }
+    }
+
+    if !features.is_empty() {
+        args.push("--features".to_string());
+        args.push(features.join(","));
+    }
+
+    Ok(args)
+}
+
+/// rustc points to the `#[cfg(feature = "...")]` of items which exist but are configured out
+fn get_gating_features(diagnostic: &Diagnostic) -> Vec<FeatureName> {
+    const PREFIX: &str = "gated behind the `";
+
+    let rendered = diagnostic
+        .rendered
+        .as_deref()
+        .unwrap_or(&diagnostic.message);
+
+    let mut features = vec![];
+
+    for (index, _) in rendered.match_indices(PREFIX) {
+        let rest = &rendered[index + PREFIX.len()..];
+
+        if let Some((feature, _)) = rest.split_once('`') {
+            if !features.iter().any(|known| known == feature) {
+                features.push(feature.to_string());
+            }
+        }
+    }
+
+    features
+}
+
+/// the first line of the error and where it happened - like the messages of failed checks
+fn describe(diagnostic: &Diagnostic) -> String {
+    let mut message = match &diagnostic.code {
+        Some(code) => format!("error[{}]: {}", code.code, diagnostic.message),
+        None => format!("error: {}", diagnostic.message),
+    };
+
+    if let Some(span) = diagnostic.spans.iter().find(|span| span.is_primary) {
+        message.push_str(&format!(
+            " ({}:{}:{})",
+            span.file_name, span.line_start, span.column_start
+        ));
+    }
+
+    message
+}
+
+fn get_host(env: &[(String, String)]) -> Result<String> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .envs(env.iter().map(|(key, value)| (key, value)))
+        .output()
+        .map_err(|err| eyre!("could not run rustc - {}", err))?;
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(|host| host.to_string())
+        .context("could not find the host target")
+}
+
+fn parse_command(command: &str) -> Result<Vec<String>> {
+    let args = shlex::split(command).ok_or(eyre!("could not parse command - {}", command))?;
+
+    if args.is_empty() {
+        bail!("command can not be empty")
+    }
+
+    Ok(args)
+}
+
+/// runs the command and kills it together with all its children if it takes longer than `timeout`
+fn run<P: AsRef<Path>>(
+    command: &[String],
+    path: P,
+    target: Option<&str>,
+    env: &[(String, String)],
+    timeout: Option<Duration>,
+    step: CheckStep,
+) -> Result<CheckResult> {
+    let Some(output) = execute(command, path, target, env, timeout)? else {
+        return Ok(CheckResult::TimedOut);
+    };
+
+    if output.is_success {
+        return Ok(CheckResult::Passed);
+    }
+
+    Ok(CheckResult::Failed(Failure {
+        step,
+        messages: summarize_output(&format!("{}{}", output.stderr, output.stdout)),
+        targets: vec![],
+        combinations: vec![],
+    }))
+}
+
+struct CommandOutput {
+    is_success: bool,
+    stdout: String,
+    stderr: String,
+}
+
+/// `None` if the command timed out
+fn execute<P: AsRef<Path>>(
+    command: &[String],
+    path: P,
+    target: Option<&str>,
+    env: &[(String, String)],
+    timeout: Option<Duration>,
+) -> Result<Option<CommandOutput>> {
+    let (program, args) = command.split_first().context("command can not be empty")?;
+
+    let mut command = Command::new(program);
+
+    // a `CARGO_TARGET_DIR` of the user would make every copy build into the same dir
+    if let Some(target_dir) = get_target_dir(path.as_ref()) {
+        command.env("CARGO_TARGET_DIR", target_dir);
+    }
+
+    command
+        .current_dir(path)
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .envs(env.iter().map(|(key, value)| (key, value)));
+
+    // also picked up by custom commands calling cargo
+    if let Some(target) = target {
+        command.env("CARGO_BUILD_TARGET", target);
+    }
+
+    // own process group, so the whole tree can be killed at once
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+
+    let mut child = {
+        // registered while locked, so an interrupt either sees the child or happens before it is started
+        let mut running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let child = command
+            .spawn()
+            .map_err(|err| eyre!("could not run {} - {}", program, err))?;
+
+        running.push(child.id());
+        child
+    };
+
+    // read the output while waiting, a full pipe would block the child
+    let stdout = read_in_background(child.stdout.take());
+    let stderr = read_in_background(child.stderr.take());
+
+    let start = Instant::now();
+
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+
+        if timeout.is_some_and(|timeout| start.elapsed() > timeout) {
+            kill_tree(child.id());
+            child.wait()?;
+            unregister(child.id());
+            return Ok(None);
+        }
+
+        thread::sleep(Duration::from_millis(50));
+    };
+
+    // e.g. by the OOM killer - the check failed, but prune can go on
+    let is_terminated = status.code().is_none();
+
+    // the rest of its process group would keep the output open
+    if is_terminated {
+        kill_tree(child.id());
+    }
+
+    unregister(child.id());
+
+    let mut stderr = stderr.join().unwrap_or_default();
+
+    if is_terminated {
+        stderr.push_str(&format!(
+            "\nerror: {} was terminated by a signal\n",
+            program
+        ));
+    }
+
+    Ok(Some(CommandOutput {
+        is_success: status.success(),
+        stdout: stdout.join().unwrap_or_default(),
+        stderr,
+    }))
+}
+
+fn read_in_background<R: Read + Send + 'static>(source: Option<R>) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut output = vec![];
+
+        if let Some(mut source) = source {
+            let _ = source.read_to_end(&mut output);
+        }
+
+        String::from_utf8_lossy(&output).to_string()
+    })
+}
+
+fn unregister(id: u32) {
+    RUNNING
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|running| *running != id);
+}
+
+/// kills every running command together with its children, used when prune gets interrupted
+///
+/// the lock is never released, so no new command is started until the process exits
+pub fn kill_running() {
+    let running = RUNNING.lock().unwrap_or_else(PoisonError::into_inner);
+
+    for id in running.iter() {
+        kill_tree(*id);
+    }
+
+    std::mem::forget(running);
+}
+
+#[cfg(unix)]
+fn kill_tree(id: u32) {
+    // the child is the leader of its process group
+    unsafe {
+        libc::kill(-(id as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_tree(id: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/T", "/F", "/PID", &id.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// picks the lines which explain the failure - compiler errors, failed tests or else the last lines
+fn summarize_output(output: &str) -> Vec<String> {
+    let lines = output.lines().collect_vec();
+
+    let mut messages = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        if !line.starts_with("error")
+            || line.starts_with("error: could not compile")
+            || line.starts_with("error: aborting due to")
+            || line.starts_with("error: test failed")
+        {
+            continue;
+        }
+
+        let mut message = line.to_string();
+
+        if let Some(location) = lines
+            .get(index + 1)
+            .filter(|next| next.trim_start().starts_with("-->"))
+        {
+            message.push_str(&format!(
+                " ({})",
+                location.trim_start().trim_start_matches("--> ")
+            ));
+        }
+
+        messages.push(message);
+    }
+
+    if messages.is_empty() {
+        messages = lines
+            .iter()
+            .filter(|line| line.contains("panicked at") || line.ends_with("FAILED"))
+            .map(|line| line.trim().to_string())
+            .collect();
+    }
+
+    if messages.is_empty() {
+        let non_empty_lines = lines
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .collect_vec();
+
+        messages = non_empty_lines
+            .iter()
+            .skip(non_empty_lines.len().saturating_sub(MAX_MESSAGES))
+            .map(|line| line.to_string())
+            .collect();
+    }
+
+    let mut unique_messages = vec![];
+
+    for message in messages {
+        if unique_messages.len() < MAX_MESSAGES && !unique_messages.contains(&message) {
+            unique_messages.push(message);
+        }
+    }
+
+    unique_messages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_gating_features, parse_combination};
+    use cargo_metadata::diagnostic::Diagnostic;
+
+    fn diagnostic(message: &str, rendered: Option<&str>) -> Diagnostic {
+        let json = serde_json::json!({
+            "message": message,
+            "code": null,
+            "level": "error",
+            "spans": [],
+            "children": [],
+            "rendered": rendered,
+        });
+
+        serde_json::from_value(json).expect("valid diagnostic")
+    }
+
+    #[test]
+    fn gating_features_are_read_from_every_note() {
+        let diagnostic = diagnostic(
+            "unresolved import `serde_json::to_writer`",
+            Some(
+                "error[E0432]: unresolved import `serde_json::to_writer`\n\
+                 note: found an item that was configured out\n\
+                 note: the item is gated behind the `std` feature\n\
+                 note: found an item that was configured out\n\
+                 note: the item is gated behind the `alloc` feature\n\
+                 note: the item is gated behind the `std` feature\n",
+            ),
+        );
+
+        assert_eq!(get_gating_features(&diagnostic), vec!["std", "alloc"]);
+    }
+
+    #[test]
+    fn gating_features_fall_back_to_the_message() {
+        let diagnostic = diagnostic("the item is gated behind the `rt` feature", None);
+
+        assert_eq!(get_gating_features(&diagnostic), vec!["rt"]);
+    }
+
+    #[test]
+    fn diagnostics_without_gating_notes_have_no_features() {
+        let diagnostic = diagnostic(
+            "mismatched types",
+            Some("error[E0308]: mismatched types\nnote: expected `u32`, found `u64`\n"),
+        );
+
+        assert!(get_gating_features(&diagnostic).is_empty());
+    }
+
+    #[test]
+    fn plain_combinations_map_to_cargo_flags() {
+        assert!(matches!(parse_combination("default").as_deref(), Ok([])));
+        assert!(matches!(
+            parse_combination("all").as_deref(),
+            Ok([flag]) if flag == "--all-features"
+        ));
+        assert!(matches!(
+            parse_combination("none").as_deref(),
+            Ok([flag]) if flag == "--no-default-features"
+        ));
+    }
+
+    #[test]
+    fn feature_lists_are_passed_together() {
+        assert!(matches!(
+            parse_combination("none, std ,alloc").as_deref(),
+            Ok([no_default, flag, features])
+                if no_default == "--no-default-features" && flag == "--features" && features == "std,alloc"
+        ));
+    }
+
+    #[test]
+    fn empty_combinations_are_rejected() {
+        assert!(parse_combination("").is_err());
+        assert!(parse_combination(" ").is_err());
+        assert!(parse_combination("std,,alloc").is_err());
+        assert!(parse_combination("std,").is_err());
+    }
+
+    #[test]
+    fn default_and_all_can_not_be_combined() {
+        assert!(parse_combination("default,std").is_err());
+        assert!(parse_combination("all,std").is_err());
+    }
+}
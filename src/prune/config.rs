@@ -0,0 +1,109 @@
+use crate::io::util::{get_item_from_doc, toml_document_from_path};
+use color_eyre::eyre::{eyre, ContextCompat};
+use color_eyre::Result;
+use std::path::Path;
+use toml_edit::TableLike;
+
+/// settings for prune defined in the root `Cargo.toml`
+///
+/// ```toml
+/// [workspace.cargo-features-manager.prune]
+/// build = "cargo clippy --all-targets -- -D warnings"
+/// test = "cargo nextest run"
+/// features = ["serde"]
+/// all-features = false
+/// feature-matrix = ["none", "default", "all", "serde,std"]
+/// build-timeout = 600
+/// test-timeout = 300
+/// retries = 2
+/// ```
+#[derive(Default)]
+pub struct PruneConfig {
+    pub build: Option<String>,
+    pub test: Option<String>,
+    pub features: Vec<String>,
+    pub all_features: bool,
+    pub feature_matrix: Vec<String>,
+    pub build_timeout: Option<u64>,
+    pub test_timeout: Option<u64>,
+    pub retries: Option<u64>,
+}
+
+impl PruneConfig {
+    pub fn load<P: AsRef<Path>>(root_path: P) -> Result<PruneConfig> {
+        let Ok(document) = toml_document_from_path(root_path.as_ref().join("Cargo.toml")) else {
+            return Ok(PruneConfig::default());
+        };
+
+        let item = get_item_from_doc("workspace.cargo-features-manager.prune", &document)
+            .or_else(|_| get_item_from_doc("cargo-features-manager.prune", &document));
+
+        let Ok(item) = item else {
+            return Ok(PruneConfig::default());
+        };
+
+        let table = item
+            .as_table_like()
+            .context("could not parse cargo-features-manager.prune - not a table")?;
+
+        Ok(PruneConfig {
+            build: get_string(table, "build")?,
+            test: get_string(table, "test")?,
+            features: get_string_array(table, "features")?,
+            all_features: get_bool(table, "all-features")?.unwrap_or(false),
+            feature_matrix: get_string_array(table, "feature-matrix")?,
+            build_timeout: get_unsigned(table, "build-timeout")?,
+            test_timeout: get_unsigned(table, "test-timeout")?,
+            retries: get_unsigned(table, "retries")?,
+        })
+    }
+}
+
+fn get_string(table: &dyn TableLike, key: &str) -> Result<Option<String>> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_str()
+                .map(|value| value.to_string())
+                .ok_or(eyre!("could not parse prune.{} - not a string", key))
+        })
+        .transpose()
+}
+
+fn get_bool(table: &dyn TableLike, key: &str) -> Result<Option<bool>> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_bool()
+                .ok_or(eyre!("could not parse prune.{} - not a bool", key))
+        })
+        .transpose()
+}
+
+fn get_unsigned(table: &dyn TableLike, key: &str) -> Result<Option<u64>> {
+    table
+        .get(key)
+        .map(|item| {
+            item.as_integer()
+                .and_then(|value| u64::try_from(value).ok())
+                .ok_or(eyre!(
+                    "could not parse prune.{} - not a positive integer",
+                    key
+                ))
+        })
+        .transpose()
+}
+
+fn get_string_array(table: &dyn TableLike, key: &str) -> Result<Vec<String>> {
+    let Some(item) = table.get(key) else {
+        return Ok(vec![]);
+    };
+
+    Ok(item
+        .as_array()
+        .ok_or(eyre!("could not parse prune.{} - not an array", key))?
+        .iter()
+        .filter_map(|value| value.as_str())
+        .map(|value| value.to_string())
+        .collect())
+}
@@ -0,0 +1,153 @@
+use color_eyre::eyre::{eyre, ContextCompat};
+use color_eyre::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// copies the sources of the project without build output, `.git` or ignored files
+/// and points its target dir to `target_dir`, so builds are incremental across runs
+pub fn copy_project(from: &Path, to: &Path, target_dir: &Path) -> Result<()> {
+    let mut files = match get_git_files(from) {
+        Some(files) => files,
+        None => {
+            let mut files = vec![];
+            collect_files(from, Path::new(""), &mut files)?;
+            files
+        }
+    };
+
+    // git lists submodules and symlinked directories as a single entry
+    let directories = files
+        .iter()
+        .filter(|file| from.join(file).is_dir())
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for directory in directories {
+        collect_files(from, &directory, &mut files)?;
+    }
+
+    for file in files.iter().chain([&PathBuf::from("Cargo.lock")]) {
+        let source = from.join(file);
+
+        if !source.is_file() {
+            continue;
+        }
+
+        let destination = to.join(file);
+
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::copy(&source, &destination)
+            .map_err(|err| eyre!("could not copy {:?} - {}", source, err))?;
+    }
+
+    set_target_dir(to, target_dir)
+}
+
+/// tracked and untracked files which are not ignored, `None` if the project is not inside a git repository
+fn get_git_files(path: &Path) -> Option<Vec<PathBuf>> {
+    let output = Command::new("git")
+        .current_dir(path)
+        .args([
+            "ls-files",
+            "-z",
+            "--cached",
+            "--others",
+            "--exclude-standard",
+        ])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    Some(
+        String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|file| !file.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+    )
+}
+
+fn collect_files(root: &Path, relative: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in fs::read_dir(root.join(relative))? {
+        let entry = entry?;
+        let path = relative.join(entry.file_name());
+
+        // follows symlinks, so linked directories are copied as well
+        if entry.path().is_dir() {
+            // cargo marks every target dir with a CACHEDIR.TAG
+            if entry.file_name() == ".git" || entry.path().join("CACHEDIR.TAG").exists() {
+                continue;
+            }
+
+            collect_files(root, &path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// the target dir `copy_project` set for the copy, `CARGO_TARGET_DIR` has to point to it as well as it overrides the config
+pub fn get_target_dir(project_path: &Path) -> Option<PathBuf> {
+    let config = fs::read_to_string(get_config_path(project_path))
+        .ok()?
+        .parse::<DocumentMut>()
+        .ok()?;
+
+    config
+        .get("build")?
+        .get("target-dir")?
+        .as_str()
+        .map(PathBuf::from)
+}
+
+fn get_config_path(project_path: &Path) -> PathBuf {
+    let cargo_dir = project_path.join(".cargo");
+
+    // cargo prefers the legacy file name if both exist
+    if cargo_dir.join("config").is_file() {
+        cargo_dir.join("config")
+    } else {
+        cargo_dir.join("config.toml")
+    }
+}
+
+fn set_target_dir(project_path: &Path, target_dir: &Path) -> Result<()> {
+    let config_path = get_config_path(project_path);
+
+    let mut config = match fs::read_to_string(&config_path) {
+        Ok(content) => content.parse::<DocumentMut>()?,
+        Err(_) => DocumentMut::new(),
+    };
+
+    let build = config
+        .entry("build")
+        .or_insert(Item::Table(Table::new()))
+        .as_table_like_mut()
+        .context("could not parse build in .cargo/config.toml - not a table")?;
+
+    build.insert(
+        "target-dir",
+        toml_edit::value(
+            target_dir
+                .to_str()
+                .context(format!("target dir is not valid utf-8 - {:?}", target_dir))?,
+        ),
+    );
+
+    fs::create_dir_all(project_path.join(".cargo"))?;
+    fs::write(config_path, config.to_string())?;
+
+    Ok(())
+}
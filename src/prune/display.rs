@@ -1,15 +1,22 @@
 use crate::project::document::Document;
-use crate::prune::{DependencyName, FeatureName, FeaturesMap};
+use crate::prune::check::Failure;
+use crate::prune::report::Verdict;
+use crate::prune::worker::{DependencyResult, Job, NO_DEFAULT_FEATURES};
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName, TargetFeaturesMap};
+use crate::MessageFormat;
 use color_eyre::Result;
 use console::{style, Term};
 use itertools::Itertools;
+use serde::Serialize;
 use std::collections::HashMap;
 use std::io::Write;
-
-type IsKnownFeature = bool;
+use std::time::Duration;
 
 pub struct Display {
     term: Term,
+    format: MessageFormat,
+    /// package & dependency keys used in json messages - the same as in the reports
+    keys: HashMap<(PackageName, DependencyName), (String, String)>,
 
     package_inset: usize,
     dependency_inset: usize,
@@ -20,14 +27,88 @@ pub struct Display {
 
     package_name: String,
     package_feature_count: usize,
-    package_checked_features_count: usize,
+    package_checked_features_count: HashMap<String, usize>,
+
+    workers: Vec<Option<WorkerState>>,
+}
+
+/// a single line of `--message-format json`
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum Message<'a> {
+    PackageStarted {
+        package: &'a str,
+        features: usize,
+    },
+    FeatureStarted {
+        package: &'a str,
+        dependency: &'a str,
+        /// multiple features are tested at once by `--strategy bisect` and `--try-no-default`
+        feature: &'a str,
+        worker: usize,
+    },
+    FeatureFinished {
+        package: &'a str,
+        dependency: &'a str,
+        feature: &'a str,
+        verdict: Verdict,
+        duration: f64,
+    },
+    DependencyFinished {
+        package: &'a str,
+        dependency: &'a str,
+        disabled: Vec<&'a str>,
+        duration: f64,
+    },
+    VerificationFinished {
+        restored: Vec<FeatureMessage<'a>>,
+    },
+    Finished {
+        disabled: Vec<FeatureMessage<'a>>,
+        test_only: Vec<FeatureMessage<'a>>,
+        target_only: Vec<FeatureMessage<'a>>,
+        duration: f64,
+    },
+}
+
+#[derive(Serialize)]
+struct FeatureMessage<'a> {
+    package: &'a str,
+    dependency: &'a str,
+    feature: &'a str,
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    targets: &'a [String],
+}
 
+struct WorkerState {
+    package_name: String,
     dependency_name: String,
     dependency_feature_count: usize,
+    feature: Option<(usize, FeatureName)>,
 }
 
 impl Display {
-    pub fn new(features_to_test: &FeaturesMap, document: &Document) -> Self {
+    pub fn new(
+        features_to_test: &FeaturesMap,
+        document: &Document,
+        worker_count: usize,
+        format: MessageFormat,
+    ) -> Result<Self> {
+        let mut keys = HashMap::new();
+
+        for (package_name, dependencies) in features_to_test {
+            for dependency_name in dependencies.keys() {
+                let dependency = document
+                    .get_package(package_name)?
+                    .get_dep(dependency_name)?;
+
+                keys.insert(
+                    (package_name.to_string(), dependency_name.to_string()),
+                    (document.get_package_key(package_name), dependency.get_key()),
+                );
+            }
+        }
+
         let feature_count = features_to_test
             .values()
             .flat_map(|dependencies| dependencies.values())
@@ -37,39 +118,466 @@ impl Display {
         let package_inset = if features_to_test.len() == 1 { 0 } else { 2 };
         let dependency_inset = if features_to_test.len() == 1 { 2 } else { 4 };
 
-        Self {
+        Ok(Self {
+            format,
+            keys,
             feature_count,
             package_inset,
             dependency_inset,
             is_workspace: document.is_workspace(),
             package_name: "?".to_string(),
             package_feature_count: 0,
-            package_checked_features_count: 0,
-            dependency_name: "?".to_string(),
+            package_checked_features_count: HashMap::new(),
             term: Term::stdout(),
             checked_features_count: 0,
-            dependency_feature_count: 0,
-        }
+            workers: (0..worker_count).map(|_| None).collect(),
+        })
+    }
+
+    fn is_json(&self) -> bool {
+        matches!(self.format, MessageFormat::Json)
+    }
+
+    /// json messages are written as single lines so they can be read one by one while prune is running
+    fn emit(&self, message: &Message) -> Result<()> {
+        writeln!(&self.term, "{}", serde_json::to_string(message)?)?;
+        Ok(())
+    }
+
+    fn get_keys<'a>(
+        &'a self,
+        package_name: &'a str,
+        dependency_name: &'a str,
+    ) -> (&'a str, &'a str) {
+        self.keys
+            .get(&(package_name.to_string(), dependency_name.to_string()))
+            .map(|(package, dependency)| (package.as_str(), dependency.as_str()))
+            .unwrap_or((package_name, dependency_name))
+    }
+
+    fn get_feature_messages<'a>(&'a self, features: &'a FeaturesMap) -> Vec<FeatureMessage<'a>> {
+        features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            .flat_map(|(package_name, dependencies)| {
+                dependencies
+                    .iter()
+                    .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                    .flat_map(move |(dependency_name, features)| {
+                        let (package, dependency) = self.get_keys(package_name, dependency_name);
+
+                        features.iter().sorted().map(move |feature| FeatureMessage {
+                            package,
+                            dependency,
+                            feature,
+                            targets: &[],
+                        })
+                    })
+            })
+            .collect()
     }
 
-    pub fn start(&self) -> Result<()> {
+    pub fn start(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
         writeln!(&self.term, "workspace [{}]", self.feature_count)?;
         self.term.hide_cursor()?;
+        self.display_workers()?;
+        Ok(())
+    }
+
+    pub fn display_no_saved_state_notice(&self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        writeln!(
+            &self.term,
+            "no saved progress matches the current project - starting from the beginning"
+        )?;
+        Ok(())
+    }
+
+    /// counts features whose result was loaded from a previous run
+    pub fn restore_features(&mut self, package_name: &str, count: usize) -> Result<()> {
+        self.checked_features_count += count;
+        *self
+            .package_checked_features_count
+            .entry(package_name.to_string())
+            .or_default() += count;
+
+        Ok(())
+    }
+
+    /// removes the progress of the workers once all features are tested
+    pub fn finish_testing(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_to_end_of_screen()?;
         Ok(())
     }
 
     pub fn finish(&self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
         self.term.show_cursor()?;
         Ok(())
     }
 
+    pub fn display_check_count(
+        &mut self,
+        strategy: &str,
+        check_count: usize,
+        candidate_count: usize,
+    ) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(self.term)?;
+
+        if check_count <= candidate_count {
+            writeln!(
+                self.term,
+                "{} needed {} checks instead of {} - saved {}",
+                strategy,
+                check_count,
+                candidate_count,
+                style(candidate_count - check_count).green()
+            )?;
+        } else {
+            writeln!(
+                self.term,
+                "{} needed {} checks instead of {} - {} more",
+                strategy,
+                check_count,
+                candidate_count,
+                style(check_count - candidate_count).red()
+            )?;
+        }
+
+        Ok(())
+    }
+
     pub fn display_known_features_notice(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
         self.term.clear_line()?;
         writeln!(self.term)?;
         writeln!(self.term, "Some features that do not affect compilation but can limit functionally where found. For more information refer to https://github.com/ToBinio/cargo-features-manager#prune")?;
         Ok(())
     }
 
+    pub fn display_inconclusive_summary(&mut self, features: &FeaturesMap) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(self.term)?;
+        writeln!(
+            self.term,
+            "The following features were kept because their check timed out:"
+        )?;
+
+        for (package_name, dependencies) in features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                let name = if self.is_workspace {
+                    format!("{}/{}", package_name, dependency_name)
+                } else {
+                    dependency_name.to_string()
+                };
+
+                writeln!(
+                    self.term,
+                    "  {}: {}",
+                    name,
+                    style(features.iter().sorted().join(", ")).yellow()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn display_test_only_summary(&self, features: &FeaturesMap, is_moved: bool) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(&self.term)?;
+
+        if is_moved {
+            writeln!(
+                &self.term,
+                "The following features are only needed by tests and were moved to [dev-dependencies]:"
+            )?;
+        } else {
+            writeln!(&self.term, "The following features are only needed by tests and could be moved to [dev-dependencies] with --move-to-dev:")?;
+        }
+
+        for (package_name, dependencies) in features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                let name = if self.is_workspace {
+                    format!("{}/{}", package_name, dependency_name)
+                } else {
+                    dependency_name.to_string()
+                };
+
+                writeln!(
+                    &self.term,
+                    "  {}: {}",
+                    name,
+                    style(features.iter().sorted().join(", ")).yellow()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn display_target_only_summary(
+        &self,
+        features: &TargetFeaturesMap,
+        is_moved: bool,
+    ) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(&self.term)?;
+
+        if is_moved {
+            writeln!(
+                &self.term,
+                "The following features are only needed on some targets and were moved to target specific tables:"
+            )?;
+        } else {
+            writeln!(&self.term, "The following features are only needed on some targets and could be moved to target specific tables with --move-to-target:")?;
+        }
+
+        for (package_name, dependencies) in features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                let name = if self.is_workspace {
+                    format!("{}/{}", package_name, dependency_name)
+                } else {
+                    dependency_name.to_string()
+                };
+
+                for (feature, targets) in features {
+                    writeln!(
+                        &self.term,
+                        "  {}: {} ({})",
+                        name,
+                        style(feature).yellow(),
+                        targets.join(", ")
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn display_prunable_summary(&self, features: &FeaturesMap) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(&self.term)?;
+        self.term.clear_line()?;
+        writeln!(&self.term, "The following features could be disabled:")?;
+
+        for (package_name, dependencies) in features
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .filter(|(_, features)| !features.is_empty())
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                writeln!(
+                    &self.term,
+                    "  {}/{}: {}",
+                    package_name,
+                    dependency_name,
+                    style(features.iter().sorted().join(", ")).red()
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn display_explanations(
+        &self,
+        explanations: &[(PackageName, DependencyName, FeatureName, Failure)],
+    ) -> Result<()> {
+        if self.is_json() || explanations.is_empty() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(&self.term)?;
+        writeln!(&self.term, "The following features have to be kept:")?;
+
+        for (package_name, dependency_name, feature, failure) in explanations {
+            let name = if self.is_workspace {
+                format!("{}/{}/{}", package_name, dependency_name, feature)
+            } else {
+                format!("{}/{}", dependency_name, feature)
+            };
+
+            let combinations = if failure.combinations.is_empty() {
+                String::new()
+            } else {
+                format!(" with the features {}", failure.combinations.join(", "))
+            };
+
+            writeln!(
+                &self.term,
+                "  {} - {} failed{}",
+                style(name).green(),
+                failure.step,
+                combinations
+            )?;
+
+            for message in &failure.messages {
+                writeln!(&self.term, "    {}", style(message).dim())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn start_baseline(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        writeln!(self.term, "checking the unmodified project")?;
+
+        Ok(())
+    }
+
+    pub fn finish_baseline(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.move_cursor_up(1)?;
+        self.term.clear_line()?;
+        writeln!(
+            self.term,
+            "checking the unmodified project - {}",
+            style("ok").green()
+        )?;
+
+        Ok(())
+    }
+
+    pub fn start_verification(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        self.term.clear_line()?;
+        writeln!(self.term)?;
+        self.term.clear_line()?;
+        writeln!(self.term, "verifying all disabled features together")?;
+        self.term.clear_line()?;
+
+        Ok(())
+    }
+
+    pub fn finish_verification(
+        &mut self,
+        restored: &[&(String, DependencyName, FeatureName)],
+    ) -> Result<()> {
+        if self.is_json() {
+            let restored = restored
+                .iter()
+                .map(|(package_name, dependency_name, feature)| {
+                    let (package, dependency) = self.get_keys(package_name, dependency_name);
+
+                    FeatureMessage {
+                        package,
+                        dependency,
+                        feature,
+                        targets: &[],
+                    }
+                })
+                .collect();
+
+            return self.emit(&Message::VerificationFinished { restored });
+        }
+
+        self.term.move_cursor_up(1)?;
+        self.term.clear_line()?;
+
+        if restored.is_empty() {
+            writeln!(
+                self.term,
+                "verifying all disabled features together - {}",
+                style("ok").green()
+            )?;
+            return Ok(());
+        }
+
+        writeln!(
+            self.term,
+            "verifying all disabled features together - {}",
+            style("failed").red()
+        )?;
+
+        for (package_name, dependency_name, feature) in restored {
+            self.term.clear_line()?;
+
+            if self.is_workspace {
+                writeln!(
+                    self.term,
+                    "  restored {}/{}/{}",
+                    package_name, dependency_name, feature
+                )?;
+            } else {
+                writeln!(self.term, "  restored {}/{}", dependency_name, feature)?;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn next_package(
         &mut self,
         package_name: &str,
@@ -77,13 +585,27 @@ impl Display {
     ) -> Result<()> {
         self.package_name = package_name.to_string();
         self.package_feature_count = package_features.values().flatten().count();
-        self.package_checked_features_count = 0;
+
+        if self.is_json() {
+            let package = self
+                .keys
+                .iter()
+                .find(|((name, _), _)| name == package_name)
+                .map(|(_, (package, _))| package.as_str())
+                .unwrap_or(package_name);
+
+            return self.emit(&Message::PackageStarted {
+                package,
+                features: self.package_feature_count,
+            });
+        }
 
         if self.is_workspace {
             let package_inset = self.package_inset;
 
             self.term.clear_line()?;
             writeln!(self.term)?;
+            self.term.clear_line()?;
             writeln!(
                 self.term,
                 "{:package_inset$}{} [{}]",
@@ -91,32 +613,91 @@ impl Display {
             )?;
         }
 
-        Ok(())
-    }
-
-    pub fn next_dependency(&mut self, name: &str, features: &[FeatureName]) {
-        self.dependency_feature_count = features.len();
-        self.dependency_name = name.to_string();
+        self.display_workers()
     }
 
-    pub fn finish_dependency(
+    pub fn next_dependency(
         &mut self,
-        features: Vec<(&FeatureName, IsKnownFeature)>,
+        worker: usize,
+        package_name: &str,
+        dependency_name: &str,
+        features: &[FeatureName],
     ) -> Result<()> {
+        self.workers[worker] = Some(WorkerState {
+            package_name: package_name.to_string(),
+            dependency_name: dependency_name.to_string(),
+            dependency_feature_count: features.len(),
+            feature: None,
+        });
+
+        self.display_workers()
+    }
+
+    pub fn finish_worker(&mut self, worker: usize) -> Result<()> {
+        self.workers[worker] = None;
+
+        self.display_workers()
+    }
+
+    pub fn finish_dependency(&mut self, job: &Job, result: &DependencyResult) -> Result<()> {
+        if self.is_json() {
+            let (package, dependency) = self.get_keys(&job.package_name, &job.dependency_name);
+
+            for feature in &job.features {
+                self.emit(&Message::FeatureFinished {
+                    package,
+                    dependency,
+                    feature,
+                    verdict: Verdict::new(feature, result, &result.to_be_disabled),
+                    duration: result
+                        .durations
+                        .get(feature)
+                        .map(|duration| duration.as_secs_f64())
+                        .unwrap_or_default(),
+                })?;
+            }
+
+            return self.emit(&Message::DependencyFinished {
+                package,
+                dependency,
+                disabled: job
+                    .features
+                    .iter()
+                    .filter(|feature| {
+                        Verdict::new(feature, result, &result.to_be_disabled) == Verdict::Disabled
+                    })
+                    .map(|feature| feature.as_str())
+                    .collect(),
+                duration: result.duration.as_secs_f64(),
+            });
+        }
+
+        let disabled = job
+            .features
+            .iter()
+            .filter(|feature| result.to_be_disabled.contains(feature))
+            .collect_vec();
+
         let mut disabled_count = style(
-            features
+            disabled
                 .iter()
-                .map(|(name, known)| {
-                    if *known {
+                .map(|name| {
+                    if result.known_features.contains(name) {
                         style(name).color256(7).to_string()
                     } else {
                         style(format!("-{}", name)).red().to_string()
                     }
                 })
+                .chain(
+                    result
+                        .inconclusive
+                        .iter()
+                        .map(|name| style(format!("?{}", name)).yellow().to_string()),
+                )
                 .join(","),
         );
 
-        if features.is_empty() {
+        if disabled.is_empty() && result.inconclusive.is_empty() {
             disabled_count = style("0".to_string());
         }
 
@@ -126,41 +707,156 @@ impl Display {
         writeln!(
             self.term,
             "{:dependency_inset$}{} [{}/{}]",
-            "", self.dependency_name, disabled_count, self.dependency_feature_count
+            "",
+            job.dependency_name,
+            disabled_count,
+            job.features.len()
         )?;
 
-        Ok(())
+        self.display_workers()
     }
 
-    pub fn next_feature(&mut self, id: usize, feature_name: &FeatureName) -> Result<()> {
-        let dependency_inset = self.dependency_inset;
+    /// the final result after the combined verification, only shown as json
+    pub fn display_finished(
+        &self,
+        to_be_disabled: &FeaturesMap,
+        test_only: &FeaturesMap,
+        target_only: &TargetFeaturesMap,
+        duration: Duration,
+    ) -> Result<()> {
+        if !self.is_json() {
+            return Ok(());
+        }
 
-        self.term.clear_line()?;
-        writeln!(
-            self.term,
-            "{:dependency_inset$}{} [{}/{}]",
-            "", self.dependency_name, id, self.dependency_feature_count,
-        )?;
-        self.term.clear_line()?;
-        writeln!(self.term, "{:dependency_inset$} └ {}", "", feature_name)?;
-        self.term.move_cursor_up(2)?;
+        let target_only = target_only
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            .flat_map(|(package_name, dependencies)| {
+                dependencies
+                    .iter()
+                    .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                    .flat_map(move |(dependency_name, features)| {
+                        let (package, dependency) = self.get_keys(package_name, dependency_name);
+
+                        features
+                            .iter()
+                            .map(move |(feature, targets)| FeatureMessage {
+                                package,
+                                dependency,
+                                feature,
+                                targets,
+                            })
+                    })
+            })
+            .collect();
+
+        self.emit(&Message::Finished {
+            disabled: self.get_feature_messages(to_be_disabled),
+            test_only: self.get_feature_messages(test_only),
+            target_only,
+            duration: duration.as_secs_f64(),
+        })
+    }
 
-        self.display_progress_bar()?;
+    pub fn next_feature(
+        &mut self,
+        worker: usize,
+        id: usize,
+        feature_name: &FeatureName,
+    ) -> Result<()> {
+        if let Some(state) = &mut self.workers[worker] {
+            state.feature = Some((id, feature_name.to_string()));
+        }
 
-        Ok(())
+        if let (true, Some(state)) = (self.is_json(), &self.workers[worker]) {
+            let (package, dependency) = self.get_keys(&state.package_name, &state.dependency_name);
+
+            return self.emit(&Message::FeatureStarted {
+                package,
+                dependency,
+                feature: feature_name,
+                worker,
+            });
+        }
+
+        self.display_workers()
     }
 
-    pub fn finish_feature(&mut self) -> Result<()> {
-        self.checked_features_count += 1;
-        self.package_checked_features_count += 1;
+    pub fn finish_defaults(
+        &mut self,
+        worker: usize,
+        is_disabled: bool,
+        duration: Duration,
+    ) -> Result<()> {
+        if let Some(state) = &mut self.workers[worker] {
+            state.feature = None;
+        }
 
-        self.display_progress_bar()?;
+        if let (true, Some(state)) = (self.is_json(), &self.workers[worker]) {
+            let (package, dependency) = self.get_keys(&state.package_name, &state.dependency_name);
+
+            return self.emit(&Message::FeatureFinished {
+                package,
+                dependency,
+                feature: NO_DEFAULT_FEATURES,
+                verdict: if is_disabled {
+                    Verdict::Disabled
+                } else {
+                    Verdict::Kept
+                },
+                duration: duration.as_secs_f64(),
+            });
+        }
 
-        Ok(())
+        self.display_workers()
+    }
+
+    pub fn finish_features(&mut self, worker: usize, count: usize) -> Result<()> {
+        self.checked_features_count += count;
+
+        if let Some(state) = &self.workers[worker] {
+            *self
+                .package_checked_features_count
+                .entry(state.package_name.to_string())
+                .or_default() += count;
+        }
+
+        self.display_workers()
     }
 
-    fn display_progress_bar(&mut self) -> Result<()> {
-        self.term.move_cursor_down(2)?;
+    /// draws the current state of every worker and the progress bar below the finished dependencies
+    fn display_workers(&mut self) -> Result<()> {
+        if self.is_json() {
+            return Ok(());
+        }
+
+        let dependency_inset = self.dependency_inset;
+
+        for worker in &self.workers {
+            self.term.clear_line()?;
+
+            let Some(state) = worker else {
+                writeln!(self.term)?;
+                self.term.clear_line()?;
+                writeln!(self.term)?;
+                continue;
+            };
+
+            let (id, feature_name) = state
+                .feature
+                .as_ref()
+                .map(|(id, name)| (*id, name.as_str()))
+                .unwrap_or((0, ""));
+
+            writeln!(
+                self.term,
+                "{:dependency_inset$}{} [{}/{}]",
+                "", state.dependency_name, id, state.dependency_feature_count,
+            )?;
+            self.term.clear_line()?;
+            writeln!(self.term, "{:dependency_inset$} └ {}", "", feature_name)?;
+        }
+
         self.term.clear_line()?;
         writeln!(self.term)?;
         self.term.clear_line()?;
@@ -174,13 +870,17 @@ impl Display {
             write!(
                 self.term,
                 " -> {} [{}/{}]",
-                self.package_name, self.package_checked_features_count, self.package_feature_count
+                self.package_name,
+                self.package_checked_features_count
+                    .get(&self.package_name)
+                    .unwrap_or(&0),
+                self.package_feature_count
             )?;
         }
 
         writeln!(self.term)?;
 
-        self.term.move_cursor_up(4)?;
+        self.term.move_cursor_up(self.workers.len() * 2 + 2)?;
         Ok(())
     }
 }
@@ -0,0 +1,64 @@
+use crate::project::dependency::Dependency;
+use crate::PruneArgs;
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use glob::Pattern;
+
+/// limits prune to the packages & dependencies selected on the command line
+pub struct Filter {
+    packages: Vec<Pattern>,
+    dependencies: Vec<Pattern>,
+    excluded: Vec<Pattern>,
+}
+
+impl Filter {
+    pub fn new(args: &PruneArgs) -> Result<Filter> {
+        Ok(Filter {
+            packages: parse_patterns(&args.package)?,
+            dependencies: parse_patterns(&args.dependency)?,
+            excluded: parse_patterns(&args.exclude)?,
+        })
+    }
+
+    /// `package_key` is the name of the package or `workspace` for `[workspace.dependencies]`
+    pub fn includes_package(&self, package_key: &str) -> bool {
+        self.packages.is_empty()
+            || self
+                .packages
+                .iter()
+                .any(|pattern| pattern.matches(package_key))
+    }
+
+    pub fn includes_dependency(&self, dependency: &Dependency) -> bool {
+        let is_selected = self.dependencies.is_empty()
+            || self
+                .dependencies
+                .iter()
+                .any(|pattern| matches_dependency(pattern, dependency));
+
+        let is_excluded = self
+            .excluded
+            .iter()
+            .any(|pattern| matches_dependency(pattern, dependency));
+
+        is_selected && !is_excluded
+    }
+}
+
+fn matches_dependency(pattern: &Pattern, dependency: &Dependency) -> bool {
+    pattern.matches(&dependency.name)
+        || pattern.matches(&dependency.get_key())
+        || dependency
+            .rename
+            .as_ref()
+            .is_some_and(|rename| pattern.matches(rename))
+}
+
+fn parse_patterns(patterns: &[String]) -> Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern).map_err(|err| eyre!("invalid pattern {} - {}", pattern, err))
+        })
+        .collect()
+}
@@ -0,0 +1,75 @@
+use crate::project::document::Document;
+use crate::prune::report::Report;
+use crate::prune::worker::{DependencyResult, Job};
+use color_eyre::eyre::{bail, eyre, ContextCompat};
+use color_eyre::Result;
+use std::path::PathBuf;
+
+/// combines the results of the reports - if they come from `--shard` runs, every shard has to be there exactly once
+pub fn merge_reports(
+    document: &Document,
+    paths: &[PathBuf],
+) -> Result<Vec<(Job, DependencyResult)>> {
+    let reports = paths
+        .iter()
+        .map(|path| Report::read(path))
+        .collect::<Result<Vec<Report>>>()?;
+
+    if let Some(shard_count) = reports
+        .iter()
+        .find_map(|report| report.shard())
+        .map(|shard| shard.count)
+    {
+        let mut indices = vec![];
+
+        for (path, report) in paths.iter().zip(&reports) {
+            let shard = report
+                .shard()
+                .context(format!("{:?} is not the report of a shard", path))?;
+
+            if shard.count != shard_count {
+                bail!(
+                    "{:?} is shard {} but the other reports are split into {} shards",
+                    path,
+                    shard,
+                    shard_count
+                );
+            }
+
+            if indices.contains(&shard.index) {
+                bail!("shard {} is given more than once", shard);
+            }
+
+            indices.push(shard.index);
+        }
+
+        if let Some(missing) = (1..=shard_count).find(|index| !indices.contains(index)) {
+            bail!("the report of shard {}/{} is missing", missing, shard_count);
+        }
+    }
+
+    let mut results: Vec<(Job, DependencyResult)> = vec![];
+
+    for (report, path) in reports.into_iter().zip(paths) {
+        let report_results = report
+            .into_results(document)
+            .map_err(|err| eyre!("invalid report {:?} - {}", path, err))?;
+
+        for (job, result) in report_results {
+            if results.iter().any(|(merged, _)| {
+                merged.package_name == job.package_name
+                    && merged.dependency_name == job.dependency_name
+            }) {
+                bail!(
+                    "{}/{} is part of more than one report",
+                    job.package_name,
+                    job.dependency_name
+                );
+            }
+
+            results.push((job, result));
+        }
+    }
+
+    Ok(results)
+}
@@ -1,66 +1,524 @@
-use crate::io::save::save_dependency;
-use crate::project::dependency::Dependency;
+use crate::io::save::{save_dependency, save_dependency_features, save_kept_features};
+use crate::project::dependency::{Dependency, DependencyType};
 use crate::project::document::Document;
+use crate::prune::check::{kill_running, CargoOptions, CheckStep, Checker};
+use crate::prune::config::PruneConfig;
+use crate::prune::copy::copy_project;
 use crate::prune::display::Display;
+use crate::prune::filter::Filter;
+use crate::prune::merge::merge_reports;
 use crate::prune::parse::get_features_to_test;
-use crate::CleanLevel;
-use color_eyre::eyre::{eyre, ContextCompat};
+use crate::prune::patch::write_patch;
+use crate::prune::report::Report;
+use crate::prune::review::{get_kept_features, Decision, Review};
+use crate::prune::shard::get_shard;
+use crate::prune::state::PruneState;
+use crate::prune::verify::verify_combined;
+use crate::prune::worker::{DependencyResult, Event, Job, Worker};
+use crate::{MessageFormat, PruneArgs, PruneCommand, PruneStrategy};
+use cargo_platform::Platform;
+use color_eyre::eyre::{bail, eyre, ContextCompat};
 use color_eyre::Result;
-use copy_dir::copy_dir;
 use itertools::Itertools;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
 use std::ops::Not;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+use std::time::Instant;
 use tempdir::TempDir;
 
 mod parse;
 
+mod patch;
+
+mod report;
+
+mod review;
+
+mod shard;
+
+mod check;
+
+mod config;
+
+mod copy;
+
 mod display;
 
+mod filter;
+
+mod merge;
+
+mod state;
+
+mod verify;
+
+mod worker;
+
 type PackageName = String;
 pub type DependencyName = String;
 pub type FeatureName = String;
 pub type FeaturesMap = HashMap<PackageName, HashMap<DependencyName, Vec<FeatureName>>>;
+/// features together with the targets they are needed for
+pub type TargetFeaturesMap =
+    HashMap<PackageName, HashMap<DependencyName, Vec<(FeatureName, Vec<String>)>>>;
+
+struct PruneResult {
+    to_be_disabled: FeaturesMap,
+    /// features which only fail the tests
+    test_only: FeaturesMap,
+    /// features which are only needed on some of the checked targets
+    target_only: TargetFeaturesMap,
+    /// every tested dependency in the order they were displayed
+    tested: Vec<(Job, DependencyResult)>,
+}
+
+/// the copies of the project, `TempDir` only removes them when dropped - which an interrupt skips
+static TEMP_DIR: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// stops the running checks and removes the copies of the project, the state for `--resume` is kept
+///
+/// returns if a prune was running
+pub fn interrupt() -> bool {
+    kill_running();
+
+    match TEMP_DIR.lock().ok().and_then(|mut path| path.take()) {
+        Some(path) => {
+            let _ = fs::remove_dir_all(path);
+            true
+        }
+        None => false,
+    }
+}
+
+pub fn prune(args: PruneArgs) -> Result<()> {
+    if args.interactive && matches!(args.message_format, MessageFormat::Json) {
+        bail!("--interactive can not be used together with --message-format json");
+    }
+
+    let start = Instant::now();
+    let cargo_options = CargoOptions::new(&args);
+    let mut main_document = cargo_options.load_document(".")?;
 
-pub fn prune(is_dry_run: bool, skip_tests: bool, clean: CleanLevel) -> Result<()> {
-    let mut main_document = Document::new(".")?;
+    let config = PruneConfig::load(main_document.root_path())?;
+    let checker = Checker::new(&args, &config)?;
+    let filter = Filter::new(&args)?;
+
+    let merged = match &args.command {
+        Some(PruneCommand::Merge { reports }) => Some(merge_reports(&main_document, reports)?),
+        None => None,
+    };
+
+    // merged results only need the combined verification
+    let job_count = if merged.is_some() { 1 } else { args.jobs.get() };
 
     let temp_dir = TempDir::new("cargo-features-manager")?;
-    let project_path = temp_dir.path().join("project");
-    copy_dir(main_document.root_path(), &project_path)?;
 
-    let mut tmp_document = Document::new(project_path)?;
+    if let Ok(mut path) = TEMP_DIR.lock() {
+        *path = Some(temp_dir.path().to_path_buf());
+    }
+
+    // every job gets its own copy of the project and target dir so builds do not block each other
+    let mut tmp_documents = (0..job_count)
+        .map(|id| {
+            let project_path = temp_dir.path().join(format!("project-{}", id));
+            let target_dir = get_data_dir(main_document.root_path()).join(format!("target-{}", id));
+
+            copy_project(main_document.root_path(), &project_path, &target_dir)?;
+
+            cargo_options.load_document(project_path)
+        })
+        .collect::<Result<Vec<Document>>>()?;
+
+    let mut state = PruneState::new(
+        &main_document,
+        &format!(
+            "{:?} {:?} {:?}",
+            checker, args.strategy, args.try_no_default
+        ),
+    )?;
+
+    let features_to_test = match merged {
+        // merged results are restored like the ones of an interrupted run, so nothing is tested again
+        Some(results) => {
+            let mut features: FeaturesMap = HashMap::new();
+
+            for (job, result) in results {
+                state.insert_result(&job, &result);
+
+                features
+                    .entry(job.package_name)
+                    .or_default()
+                    .insert(job.dependency_name, job.features);
+            }
+
+            features
+        }
+        None => {
+            let features = get_features_to_test(&tmp_documents[0], &filter)?;
+
+            match &args.shard {
+                Some(shard) => get_shard(features, shard),
+                None => features,
+            }
+        }
+    };
+
+    let mut display = Display::new(
+        &features_to_test,
+        &tmp_documents[0],
+        job_count,
+        args.message_format.clone(),
+    )?;
+
+    if args.resume && args.command.is_none() && !state.load()? {
+        display.display_no_saved_state_notice()?;
+    }
+
+    // a failing project would make every feature look required
+    if args.command.is_none() {
+        display.start_baseline()?;
+        checker.check_baseline(tmp_documents[0].root_path())?;
+        display.finish_baseline()?;
+    }
+
+    display.start()?;
 
-    let features_to_test = get_features_to_test(&tmp_document)?;
-    let to_be_disabled = prune_features(
-        &mut tmp_document,
-        skip_tests,
-        clean,
+    let PruneResult {
+        to_be_disabled,
+        test_only,
+        target_only,
+        tested,
+    } = prune_features(
+        &mut tmp_documents,
+        &mut display,
+        &args,
+        &checker,
+        &mut state,
         features_to_test,
         known_features()?,
     )?;
 
-    if is_dry_run {
-        return Ok(());
+    let to_be_disabled = verify_combined(
+        &mut tmp_documents[0],
+        &mut display,
+        &checker,
+        to_be_disabled,
+    )?;
+
+    if args.command.is_none() {
+        state.remove()?;
+    }
+
+    display.finish()?;
+
+    display.display_finished(&to_be_disabled, &test_only, &target_only, start.elapsed())?;
+
+    let move_to_dev = args.move_to_dev && !args.check && !args.dry_run;
+    let move_to_target = args.move_to_target && !args.check && !args.dry_run;
+
+    if test_only.is_empty().not() {
+        display.display_test_only_summary(&test_only, move_to_dev)?;
+    }
+
+    if target_only.is_empty().not() {
+        display.display_target_only_summary(&target_only, move_to_target)?;
     }
 
-    for (package_name, dependency) in to_be_disabled {
+    if args.check {
+        let prunable_count = to_be_disabled
+            .values()
+            .flat_map(|dependencies| dependencies.values())
+            .flatten()
+            .count();
+
+        if prunable_count > 0 {
+            display.display_prunable_summary(&to_be_disabled)?;
+            write_report(
+                &args,
+                &main_document,
+                &tested,
+                &to_be_disabled,
+                &to_be_disabled,
+                start,
+            )?;
+            bail!("{} features could be disabled", prunable_count);
+        }
+
+        return write_report(
+            &args,
+            &main_document,
+            &tested,
+            &to_be_disabled,
+            &to_be_disabled,
+            start,
+        );
+    }
+
+    if args.dry_run {
+        return write_report(
+            &args,
+            &main_document,
+            &tested,
+            &to_be_disabled,
+            &to_be_disabled,
+            start,
+        );
+    }
+
+    // with --emit-patch every change is made to a copy of the project and only written as a diff
+    let mut patch_document = match &args.emit_patch {
+        Some(_) => {
+            let patch_path = temp_dir.path().join("patch");
+
+            copy_project(
+                main_document.root_path(),
+                &patch_path,
+                &get_data_dir(main_document.root_path()).join("target-0"),
+            )?;
+
+            Some(cargo_options.load_document(patch_path)?)
+        }
+        None => None,
+    };
+
+    let document = match &mut patch_document {
+        Some(document) => document,
+        None => &mut main_document,
+    };
+
+    let proposed = to_be_disabled;
+
+    let to_be_disabled = if args.interactive {
+        let Some(entries) = Review::new(document, &proposed).start()? else {
+            return write_report(
+                &args,
+                &main_document,
+                &tested,
+                &proposed,
+                &HashMap::new(),
+                start,
+            );
+        };
+
+        for ((package_name, dependency_name), features) in get_kept_features(&entries) {
+            keep_features(document, package_name, dependency_name, &features)?;
+        }
+
+        let mut accepted: FeaturesMap = HashMap::new();
+
+        for entry in entries
+            .into_iter()
+            .filter(|entry| entry.decision == Decision::Accept)
+        {
+            accepted
+                .entry(entry.package_name)
+                .or_default()
+                .entry(entry.dependency_name)
+                .or_default()
+                .push(entry.feature);
+        }
+
+        accepted
+    } else {
+        proposed.clone()
+    };
+
+    for (package_name, dependency) in &to_be_disabled {
         for (dependency_name, features) in dependency {
             for feature in features {
-                main_document
-                    .get_package_mut(&package_name)?
-                    .get_dep_mut(&dependency_name)?
-                    .disable_feature(&feature)?;
+                document
+                    .get_package_mut(package_name)?
+                    .get_dep_mut(dependency_name)?
+                    .disable_feature(feature)?;
+            }
+
+            save_dependency(document, package_name, dependency_name)?;
+        }
+    }
+
+    if move_to_dev {
+        for (package_name, dependencies) in test_only {
+            for (dependency_name, features) in dependencies {
+                let target = document
+                    .get_package(&package_name)?
+                    .get_dep(&dependency_name)?
+                    .target
+                    .clone();
+
+                move_features(
+                    document,
+                    &package_name,
+                    &dependency_name,
+                    features,
+                    &DependencyType::Development,
+                    &[target],
+                )?;
             }
+        }
+    }
 
-            save_dependency(&mut main_document, &package_name, &dependency_name)?;
+    if move_to_target {
+        for (package_name, dependencies) in target_only {
+            for (dependency_name, features) in dependencies {
+                for (targets, features) in &features
+                    .into_iter()
+                    .sorted_by(|(_, targets_a), (_, targets_b)| targets_a.cmp(targets_b))
+                    .chunk_by(|(_, targets)| targets.clone())
+                {
+                    let kind = match document
+                        .get_package(&package_name)?
+                        .get_dep(&dependency_name)?
+                        .kind
+                    {
+                        DependencyType::Development => DependencyType::Development,
+                        DependencyType::Build => DependencyType::Build,
+                        _ => DependencyType::Normal,
+                    };
+
+                    move_features(
+                        document,
+                        &package_name,
+                        &dependency_name,
+                        features.map(|(feature, _)| feature).collect(),
+                        &kind,
+                        &targets
+                            .into_iter()
+                            .map(|target| Some(Platform::Name(target)))
+                            .collect_vec(),
+                    )?;
+                }
+            }
         }
     }
 
+    if let (Some(path), Some(patch_document)) = (&args.emit_patch, &patch_document) {
+        write_patch(&main_document, patch_document, path)?;
+    }
+
+    // only now the review & the moves are done
+    write_report(
+        &args,
+        &main_document,
+        &tested,
+        &proposed,
+        &to_be_disabled,
+        start,
+    )
+}
+
+fn write_report(
+    args: &PruneArgs,
+    document: &Document,
+    tested: &[(Job, DependencyResult)],
+    proposed: &FeaturesMap,
+    to_be_disabled: &FeaturesMap,
+    start: Instant,
+) -> Result<()> {
+    if args.report.is_empty() {
+        return Ok(());
+    }
+
+    let shard = args.shard.filter(|_| args.command.is_none());
+
+    Report::new(
+        document,
+        tested,
+        proposed,
+        to_be_disabled,
+        shard,
+        start.elapsed(),
+    )?
+    .write(&args.report)
+}
+
+/// disables the features and enables them for the same crate in the dependency tables of `kind` & `targets` instead
+fn move_features(
+    document: &mut Document,
+    package_name: &str,
+    dependency_name: &str,
+    features: Vec<FeatureName>,
+    kind: &DependencyType,
+    targets: &[Option<Platform>],
+) -> Result<()> {
+    let dependency = document
+        .get_package(package_name)?
+        .get_dep(dependency_name)?;
+
+    // features still required by other enabled features can not be moved on their own
+    let features = features
+        .into_iter()
+        .filter(|feature| {
+            dependency
+                .get_currently_dependent_features(feature)
+                .is_empty()
+        })
+        .collect_vec();
+
+    if features.is_empty() {
+        return Ok(());
+    }
+
+    for feature in &features {
+        document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?
+            .disable_feature(feature)?;
+    }
+
+    save_dependency(document, package_name, dependency_name)?;
+
+    for target in targets {
+        save_dependency_features(
+            document,
+            package_name,
+            dependency_name,
+            kind,
+            target,
+            &features,
+        )?;
+    }
+
     Ok(())
 }
 
+/// writes the features into `cargo-features-manager.keep` of the package they belong to
+fn keep_features(
+    document: &Document,
+    package_name: &str,
+    dependency_name: &str,
+    features: &[&FeatureName],
+) -> Result<()> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dependency_name)?;
+
+    let is_workspace = document.workspace_index().is_some_and(|index| {
+        document
+            .get_package_by_id(index)
+            .is_ok_and(|workspace| workspace.name == package.name)
+    });
+
+    let table_path = if is_workspace {
+        "workspace.cargo-features-manager.keep"
+    } else {
+        "cargo-features-manager.keep"
+    };
+
+    save_kept_features(
+        &package.manifest_path,
+        table_path,
+        &dependency.name,
+        features,
+    )
+}
+
+/// everything prune keeps between runs lives here
+fn get_data_dir(root_path: &Path) -> PathBuf {
+    root_path.join("target").join("cargo-features-manager")
+}
+
 //give a map of known features that do not affect completion but remove functionality
 pub fn known_features() -> Result<HashMap<String, Vec<String>>> {
     let file = include_str!("../../Known-Features.toml");
@@ -87,120 +545,276 @@ pub fn known_features() -> Result<HashMap<String, Vec<String>>> {
 }
 
 fn prune_features(
-    document: &mut Document,
-    skip_tests: bool,
-    should_clean: CleanLevel,
+    documents: &mut [Document],
+    display: &mut Display,
+    args: &PruneArgs,
+    checker: &Checker,
+    state: &mut PruneState,
     features: FeaturesMap,
     known_features: HashMap<String, Vec<String>>,
-) -> Result<FeaturesMap> {
+) -> Result<PruneResult> {
     let mut features_map = HashMap::new();
 
     let mut has_known_features_enabled = false;
-
-    let mut display = Display::new(&features, document);
-    display.start()?;
-
-    for (package_name, dependencies) in features
-        .into_iter()
+    let mut check_count = 0;
+    let mut candidate_count = 0;
+    let mut explanations = vec![];
+    let mut inconclusive_map: FeaturesMap = HashMap::new();
+    let mut test_only_map: FeaturesMap = HashMap::new();
+    let mut target_only_map: TargetFeaturesMap = HashMap::new();
+    let mut tested = vec![];
+
+    let jobs = features
+        .iter()
         .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
-    {
-        if dependencies.is_empty() {
-            continue;
+        .flat_map(|(package_name, dependencies)| {
+            dependencies
+                .iter()
+                .filter(|(_, features)| features.is_empty().not())
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                .map(|(dependency_name, features)| Job {
+                    package_name: package_name.to_string(),
+                    dependency_name: dependency_name.to_string(),
+                    features: features.clone(),
+                })
+        })
+        .collect_vec();
+
+    // only features of normal dependencies can be moved to `[dev-dependencies]`
+    // and only features of dependencies which are not target specific yet to `[target.<TARGET>.dependencies]`
+    let movable = jobs
+        .iter()
+        .map(|job| {
+            let dependency = documents[0]
+                .get_package(&job.package_name)?
+                .get_dep(&job.dependency_name)?;
+
+            Ok((
+                matches!(dependency.kind, DependencyType::Normal),
+                dependency.target.is_none()
+                    && !matches!(dependency.kind, DependencyType::Workspace),
+            ))
+        })
+        .collect::<Result<Vec<(bool, bool)>>>()?;
+
+    // results are displayed in order, no matter which worker finishes first
+    let mut started = vec![false; jobs.len()];
+    let mut results = jobs.iter().map(|_| None).collect_vec();
+    let mut queue = VecDeque::new();
+
+    for (id, job) in jobs.iter().enumerate() {
+        match state.get_result(job) {
+            Some(result) => {
+                started[id] = true;
+                results[id] = Some(result.clone());
+                display.restore_features(&job.package_name, job.features.len())?;
+            }
+            None => queue.push_back((id, job.clone())),
         }
+    }
 
-        display.next_package(&package_name, &dependencies)?;
+    let queue = Mutex::new(queue);
+    let (sender, receiver) = mpsc::channel();
+
+    thread::scope(|scope| -> Result<()> {
+        let handles = documents
+            .iter_mut()
+            .enumerate()
+            .map(|(id, document)| {
+                let worker = Worker {
+                    id,
+                    document,
+                    queue: &queue,
+                    sender: sender.clone(),
+                    args,
+                    checker,
+                    known_features: &known_features,
+                };
+
+                scope.spawn(move || worker.run())
+            })
+            .collect_vec();
 
-        for (dependency_name, features) in dependencies
-            .into_iter()
-            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
-        {
-            if features.is_empty() {
-                continue;
-            }
+        drop(sender);
 
-            let mut known_features_list = vec![];
-            let dependency = document
-                .get_package(&package_name)?
-                .get_dep(&dependency_name)?;
-
-            for feature_name in known_features.get(&dependency_name).unwrap_or(&vec![]) {
-                set_features_to_be_kept(
-                    dependency,
-                    feature_name.to_string(),
-                    &mut known_features_list,
-                )
-            }
+        let mut displayed_count = 0;
+        let mut current_package = None;
+        let mut events = receiver.into_iter();
 
-            let mut to_be_disabled = vec![];
-            to_be_disabled.append(&mut known_features_list.clone());
+        loop {
+            while let Some(job) = jobs.get(displayed_count) {
+                if started[displayed_count] && current_package != Some(&job.package_name) {
+                    display.next_package(&job.package_name, &features[&job.package_name])?;
+                    current_package = Some(&job.package_name);
+                }
 
-            display.next_dependency(&dependency_name, &features);
+                let Some(result) = results[displayed_count].take() else {
+                    break;
+                };
 
-            for (id, feature) in features.iter().enumerate() {
-                display.next_feature(id, feature)?;
+                tested.push((job.clone(), result.clone()));
 
-                document
-                    .get_package_mut(&package_name)?
-                    .get_dep_mut(&dependency_name)?
-                    .disable_feature(feature)?;
+                display.finish_dependency(job, &result)?;
 
-                save_dependency(document, &package_name, &dependency_name)?;
+                let DependencyResult {
+                    to_be_disabled,
+                    known_features: known_features_list,
+                    check_count: dependency_check_count,
+                    candidate_count: dependency_candidate_count,
+                    failures,
+                    inconclusive,
+                    ..
+                } = result;
 
-                if !to_be_disabled.contains(feature) && check(skip_tests, document.root_path())? {
-                    set_features_to_be_disabled(
-                        document
-                            .get_package(&package_name)?
-                            .get_dep(&dependency_name)?,
-                        feature.to_string(),
-                        &mut to_be_disabled,
-                    );
+                if job.features.iter().any(|feature| {
+                    to_be_disabled.contains(feature) && known_features_list.contains(feature)
+                }) {
+                    has_known_features_enabled = true;
                 }
 
-                //reset to start
-                for feature in &features {
-                    document
-                        .get_package_mut(&package_name)?
-                        .get_dep_mut(&dependency_name)?
-                        .enable_feature(feature)?;
+                if inconclusive.is_empty().not() {
+                    inconclusive_map
+                        .entry(job.package_name.to_string())
+                        .or_default()
+                        .insert(job.dependency_name.to_string(), inconclusive);
                 }
 
-                save_dependency(document, &package_name, &dependency_name)?;
+                // `default` is never tested itself, it gets disabled together with the default features
+                let to_be_disabled = to_be_disabled
+                    .into_iter()
+                    .filter(|feature| known_features_list.contains(feature).not())
+                    .filter(|feature| feature != "default")
+                    .collect_vec();
+
+                features_map
+                    .entry(job.package_name.to_string())
+                    .or_insert_with(HashMap::new)
+                    .insert(job.dependency_name.to_string(), to_be_disabled);
+
+                check_count += dependency_check_count;
+                candidate_count += dependency_candidate_count;
+
+                let test_only = job
+                    .features
+                    .iter()
+                    .filter(|feature| {
+                        failures
+                            .get(*feature)
+                            .is_some_and(|failure| failure.step.is_test_only())
+                    })
+                    .cloned()
+                    .collect_vec();
+
+                let target_only = job
+                    .features
+                    .iter()
+                    .filter_map(|feature| {
+                        failures
+                            .get(feature)
+                            .filter(|failure| {
+                                failure.step == CheckStep::Build
+                                    && failure.targets.is_empty().not()
+                                    && failure.targets.len() < checker.targets().len()
+                            })
+                            .map(|failure| (feature.to_string(), failure.targets.clone()))
+                    })
+                    .collect_vec();
+
+                let (movable_to_dev, movable_to_target) = movable[displayed_count];
+
+                if movable_to_target && target_only.is_empty().not() {
+                    target_only_map
+                        .entry(job.package_name.to_string())
+                        .or_default()
+                        .insert(job.dependency_name.to_string(), target_only);
+                }
 
-                display.finish_feature()?;
-            }
+                if movable_to_dev && test_only.is_empty().not() {
+                    test_only_map
+                        .entry(job.package_name.to_string())
+                        .or_default()
+                        .insert(job.dependency_name.to_string(), test_only);
+                }
 
-            let features_result = features
-                .iter()
-                .filter(|feature| to_be_disabled.contains(feature))
-                .map(|feature| {
-                    if known_features_list.contains(feature) {
-                        has_known_features_enabled = true;
-                        (feature, true)
-                    } else {
-                        (feature, false)
+                for feature in &job.features {
+                    if let Some(failure) = failures.get(feature) {
+                        explanations.push((
+                            job.package_name.to_string(),
+                            job.dependency_name.to_string(),
+                            feature.to_string(),
+                            failure.clone(),
+                        ));
                     }
-                })
-                .collect();
-
-            display.finish_dependency(features_result)?;
+                }
 
-            if let CleanLevel::Dependency = should_clean {
-                clean(document.root_path())?;
+                displayed_count += 1;
             }
 
-            let to_be_disabled = to_be_disabled
-                .into_iter()
-                .filter(|feature| known_features_list.contains(feature).not())
-                .collect_vec();
+            let Some(event) = events.next() else {
+                break;
+            };
+
+            match event {
+                Event::DependencyStarted { worker, job } => {
+                    started[job] = true;
+                    display.next_dependency(
+                        worker,
+                        &jobs[job].package_name,
+                        &jobs[job].dependency_name,
+                        &jobs[job].features,
+                    )?;
+                }
+                Event::FeatureStarted {
+                    worker,
+                    id,
+                    feature,
+                } => display.next_feature(worker, id, &feature)?,
+                Event::FeaturesFinished { worker, count } => {
+                    display.finish_features(worker, count)?
+                }
+                Event::DefaultsFinished {
+                    worker,
+                    is_disabled,
+                    duration,
+                } => display.finish_defaults(worker, is_disabled, duration)?,
+                Event::DependencyFinished {
+                    worker,
+                    job,
+                    result,
+                } => {
+                    state.add_result(&jobs[job], &result)?;
+                    results[job] = Some(result);
+                    display.finish_worker(worker)?;
+                }
+            }
+        }
 
-            features_map
-                .entry(package_name.to_string())
-                .or_insert_with(HashMap::new)
-                .insert(dependency_name, to_be_disabled);
+        for handle in handles {
+            handle
+                .join()
+                .map_err(|_| eyre!("a prune worker panicked"))??;
         }
 
-        if let CleanLevel::Package = should_clean {
-            clean(document.root_path())?;
+        Ok(())
+    })?;
+
+    display.finish_testing()?;
+
+    if inconclusive_map.is_empty().not() {
+        display.display_inconclusive_summary(&inconclusive_map)?;
+    }
+
+    if args.explain {
+        display.display_explanations(&explanations)?;
+    }
+
+    match args.strategy {
+        PruneStrategy::Linear => {}
+        PruneStrategy::Bisect => {
+            display.display_check_count("bisect", check_count, candidate_count)?
+        }
+        PruneStrategy::Minimize => {
+            display.display_check_count("minimize", check_count, candidate_count)?
         }
     }
 
@@ -208,9 +822,12 @@ fn prune_features(
         display.display_known_features_notice()?;
     }
 
-    display.finish()?;
-
-    Ok(features_map)
+    Ok(PruneResult {
+        to_be_disabled: features_map,
+        test_only: test_only_map,
+        target_only: target_only_map,
+        tested,
+    })
 }
 
 fn set_features_to_be_disabled(
@@ -254,56 +871,3 @@ fn set_features_to_be_kept(
         }
     }
 }
-
-fn clean<P: AsRef<Path>>(path: P) -> Result<()> {
-    let mut child = Command::new("cargo")
-        .current_dir(path)
-        .arg("clean")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    let _ = child.wait()?.code().ok_or(eyre!("Could not clear"))?;
-
-    Ok(())
-}
-
-fn check<P: AsRef<Path>>(skip_tests: bool, path: P) -> Result<bool> {
-    if !build(&path)? {
-        return Ok(false);
-    }
-
-    if !skip_tests && !test(&path)? {
-        return Ok(false);
-    }
-
-    Ok(true)
-}
-
-fn build<P: AsRef<Path>>(path: P) -> Result<bool> {
-    let mut child = Command::new("cargo")
-        .current_dir(path)
-        .arg("build")
-        .arg("--all-targets")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    let code = child.wait()?.code().ok_or(eyre!("Could not build"))?;
-
-    Ok(code == 0)
-}
-
-fn test<P: AsRef<Path>>(path: P) -> Result<bool> {
-    let mut child = Command::new("cargo")
-        .current_dir(path)
-        .arg("test")
-        .arg("--workspace")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
-
-    let code = child.wait()?.code().ok_or(eyre!("Could not test"))?;
-
-    Ok(code == 0)
-}
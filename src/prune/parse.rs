@@ -3,29 +3,38 @@ use color_eyre::Result;
 use crate::io::util::{get_item_from_doc, toml_document_from_path};
 use crate::project::dependency::Dependency;
 use crate::project::document::Document;
+use crate::prune::filter::Filter;
 use crate::prune::FeaturesMap;
 use color_eyre::eyre::{eyre, ContextCompat};
 use std::collections::HashMap;
 use std::ops::Not;
 use std::path::Path;
 
-pub fn get_features_to_test(document: &Document) -> Result<FeaturesMap> {
+pub fn get_features_to_test(document: &Document, filter: &Filter) -> Result<FeaturesMap> {
     let base_ignored_features =
         get_ignored_features("./", "workspace.cargo-features-manager.keep")?;
 
-    let mut enabled_features = get_enabled_features(document);
+    let mut enabled_features = get_enabled_features(document, filter);
     remove_ignored_features(document, &base_ignored_features, &mut enabled_features)?;
 
     Ok(enabled_features)
 }
 
-fn get_enabled_features(document: &Document) -> FeaturesMap {
+fn get_enabled_features(document: &Document, filter: &Filter) -> FeaturesMap {
     let mut data = HashMap::new();
 
-    for package in document.get_packages() {
+    for package in document
+        .get_packages()
+        .iter()
+        .filter(|package| filter.includes_package(&document.get_package_key(&package.name)))
+    {
         let mut package_data = HashMap::new();
 
-        for dependency in package.get_deps() {
+        for dependency in package
+            .get_deps()
+            .iter()
+            .filter(|dependency| filter.includes_dependency(dependency))
+        {
             let enabled_features = dependency
                 .features
                 .iter()
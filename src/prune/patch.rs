@@ -0,0 +1,52 @@
+use crate::project::document::Document;
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use itertools::Itertools;
+use similar::TextDiff;
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+/// writes the changes made to the manifests of the copy `edited` as a unified diff relative to the project root,
+/// so it can be applied with `git apply` or `patch -p1`
+pub fn write_patch(original: &Document, edited: &Document, path: &Path) -> Result<()> {
+    let mut patch = String::new();
+
+    for manifest_path in original
+        .get_packages()
+        .iter()
+        .map(|package| &package.manifest_path)
+        .sorted()
+        .dedup()
+    {
+        let relative_path = Path::new(manifest_path)
+            .strip_prefix(original.root_path())
+            .map_err(|_| eyre!("{} is not inside of the project", manifest_path))?;
+
+        let old = fs::read_to_string(manifest_path)?;
+        let new = fs::read_to_string(edited.root_path().join(relative_path))?;
+
+        if old == new {
+            continue;
+        }
+
+        let name = relative_path.to_string_lossy().replace('\\', "/");
+
+        write!(
+            patch,
+            "{}",
+            TextDiff::from_lines(&old, &new)
+                .unified_diff()
+                .header(&format!("a/{}", name), &format!("b/{}", name))
+        )?;
+    }
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+
+    fs::write(path, patch).map_err(|err| eyre!("could not write patch to {:?} - {}", path, err))
+}
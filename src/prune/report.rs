@@ -0,0 +1,445 @@
+use crate::project::document::Document;
+use crate::prune::check::{CheckStep, Failure};
+use crate::prune::worker::{DependencyResult, Job};
+use crate::prune::{set_features_to_be_disabled, FeatureName, FeaturesMap};
+use crate::{ReportFormat, Shard};
+use color_eyre::eyre::{bail, eyre};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// final result of a prune run, durations are in seconds
+#[derive(Serialize, Deserialize)]
+pub struct Report {
+    version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    shard: Option<Shard>,
+    duration: f64,
+    summary: Summary,
+    dependencies: Vec<DependencyReport>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Summary {
+    tested: usize,
+    disabled: usize,
+    kept: usize,
+    known_false_positive: usize,
+    inconclusive: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DependencyReport {
+    package: String,
+    dependency: String,
+    duration: f64,
+    features: Vec<FeatureReport>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct FeatureReport {
+    name: String,
+    verdict: Verdict,
+    duration: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    step: Option<CheckStep>,
+    /// the targets the build failed for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    targets: Vec<String>,
+    /// the combinations of the feature matrix the check failed for
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    combinations: Vec<String>,
+    messages: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Disabled,
+    Kept,
+    KnownFalsePositive,
+    Inconclusive,
+}
+
+impl std::fmt::Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Verdict::Disabled => f.write_str("disabled"),
+            Verdict::Kept => f.write_str("kept"),
+            Verdict::KnownFalsePositive => f.write_str("known false positive"),
+            Verdict::Inconclusive => f.write_str("inconclusive"),
+        }
+    }
+}
+
+impl Verdict {
+    /// `disabled` are the features of the dependency which end up disabled
+    pub fn new(feature: &FeatureName, result: &DependencyResult, disabled: &[FeatureName]) -> Self {
+        if result.known_features.contains(feature) {
+            Verdict::KnownFalsePositive
+        } else if result.inconclusive.contains(feature) {
+            Verdict::Inconclusive
+        } else if disabled.contains(feature) {
+            Verdict::Disabled
+        } else {
+            Verdict::Kept
+        }
+    }
+}
+
+impl Report {
+    /// `proposed` are the features prune found, `to_be_disabled` the ones left after the interactive review
+    pub fn new(
+        document: &Document,
+        tested: &[(Job, DependencyResult)],
+        proposed: &FeaturesMap,
+        to_be_disabled: &FeaturesMap,
+        shard: Option<Shard>,
+        duration: Duration,
+    ) -> Result<Report> {
+        let mut summary = Summary::default();
+        let mut dependencies = vec![];
+
+        for (job, result) in tested {
+            let dependency = document
+                .get_package(&job.package_name)?
+                .get_dep(&job.dependency_name)?;
+
+            let disabled = get_features(to_be_disabled, job);
+            let proposed = get_features(proposed, job);
+
+            let mut features = vec![];
+
+            for feature in &job.features {
+                let failure = result.failures.get(feature);
+
+                let verdict = Verdict::new(feature, result, disabled);
+
+                let messages = match failure {
+                    Some(failure) => failure.messages.clone(),
+                    None if verdict == Verdict::Kept && proposed.contains(feature) => {
+                        vec!["kept in the interactive review".to_string()]
+                    }
+                    None if verdict == Verdict::Kept => {
+                        vec!["restored by the combined verification".to_string()]
+                    }
+                    None => vec![],
+                };
+
+                summary.tested += 1;
+
+                match verdict {
+                    Verdict::Disabled => summary.disabled += 1,
+                    Verdict::Kept => summary.kept += 1,
+                    Verdict::KnownFalsePositive => summary.known_false_positive += 1,
+                    Verdict::Inconclusive => summary.inconclusive += 1,
+                }
+
+                features.push(FeatureReport {
+                    name: feature.to_string(),
+                    verdict,
+                    duration: result
+                        .durations
+                        .get(feature)
+                        .map(|duration| duration.as_secs_f64())
+                        .unwrap_or_default(),
+                    step: failure.map(|failure| failure.step),
+                    targets: failure
+                        .map(|failure| failure.targets.clone())
+                        .unwrap_or_default(),
+                    combinations: failure
+                        .map(|failure| failure.combinations.clone())
+                        .unwrap_or_default(),
+                    messages,
+                });
+            }
+
+            dependencies.push(DependencyReport {
+                package: document.get_package_key(&job.package_name),
+                dependency: dependency.get_key(),
+                duration: result.duration.as_secs_f64(),
+                features,
+            });
+        }
+
+        Ok(Report {
+            version: 1,
+            shard,
+            duration: duration.as_secs_f64(),
+            summary,
+            dependencies,
+        })
+    }
+
+    pub fn read(path: &Path) -> Result<Report> {
+        let content = fs::read_to_string(path)
+            .map_err(|err| eyre!("could not read report {:?} - {}", path, err))?;
+
+        serde_json::from_str(&content)
+            .map_err(|err| eyre!("could not parse report {:?} - {}", path, err))
+    }
+
+    pub fn shard(&self) -> Option<Shard> {
+        self.shard
+    }
+
+    /// turns the report back into the results of the tested dependencies of `document`
+    pub fn into_results(self, document: &Document) -> Result<Vec<(Job, DependencyResult)>> {
+        let mut results = vec![];
+
+        for dependency_report in self.dependencies {
+            let package = document.get_package_by_key(&dependency_report.package)?;
+            let dependency = package.get_dep_by_key(&dependency_report.dependency)?;
+
+            let mut result = DependencyResult {
+                to_be_disabled: vec![],
+                known_features: vec![],
+                check_count: 0,
+                candidate_count: 0,
+                failures: HashMap::new(),
+                inconclusive: vec![],
+                durations: HashMap::new(),
+                duration: get_duration(dependency_report.duration)?,
+            };
+
+            for feature in &dependency_report.features {
+                if dependency.get_feature(&feature.name).is_none() {
+                    bail!(
+                        "{}/{} has no feature {} - the report belongs to a different project state",
+                        dependency_report.package,
+                        dependency_report.dependency,
+                        feature.name
+                    );
+                }
+
+                result
+                    .durations
+                    .insert(feature.name.to_string(), get_duration(feature.duration)?);
+
+                match feature.verdict {
+                    Verdict::Disabled => set_features_to_be_disabled(
+                        dependency,
+                        feature.name.to_string(),
+                        &mut result.to_be_disabled,
+                    ),
+                    Verdict::KnownFalsePositive => {
+                        result.known_features.push(feature.name.to_string());
+                        result.to_be_disabled.push(feature.name.to_string());
+                    }
+                    Verdict::Inconclusive => result.inconclusive.push(feature.name.to_string()),
+                    Verdict::Kept => {
+                        if let Some(step) = feature.step {
+                            result.failures.insert(
+                                feature.name.to_string(),
+                                Failure {
+                                    step,
+                                    messages: feature.messages.clone(),
+                                    targets: feature.targets.clone(),
+                                    combinations: feature.combinations.clone(),
+                                },
+                            );
+                        }
+                    }
+                }
+            }
+
+            results.push((
+                Job {
+                    package_name: package.name.to_string(),
+                    dependency_name: dependency.get_name(),
+                    features: dependency_report
+                        .features
+                        .into_iter()
+                        .map(|feature| feature.name)
+                        .collect(),
+                },
+                result,
+            ));
+        }
+
+        Ok(results)
+    }
+
+    pub fn write(&self, reports: &[(ReportFormat, PathBuf)]) -> Result<()> {
+        for (format, path) in reports {
+            let content = match format {
+                ReportFormat::Json => serde_json::to_string_pretty(self)?,
+                ReportFormat::Markdown => self.to_markdown()?,
+                ReportFormat::Junit => self.to_junit()?,
+            };
+
+            if let Some(parent) = path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+            {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(path, content)
+                .map_err(|err| eyre!("could not write report to {:?} - {}", path, err))?;
+        }
+
+        Ok(())
+    }
+
+    fn to_markdown(&self) -> Result<String> {
+        let mut markdown = String::new();
+
+        writeln!(markdown, "## cargo features prune")?;
+        writeln!(markdown)?;
+        writeln!(
+            markdown,
+            "{} features tested in {:.1}s - {} disabled, {} kept, {} known false positives, {} inconclusive",
+            self.summary.tested,
+            self.duration,
+            self.summary.disabled,
+            self.summary.kept,
+            self.summary.known_false_positive,
+            self.summary.inconclusive
+        )?;
+        writeln!(markdown)?;
+        writeln!(
+            markdown,
+            "| package | dependency | feature | result | duration | reason |"
+        )?;
+        writeln!(markdown, "|---|---|---|---|---:|---|")?;
+
+        for dependency in &self.dependencies {
+            for feature in &dependency.features {
+                let messages = feature
+                    .messages
+                    .iter()
+                    .map(|message| escape_markdown(message))
+                    .join("<br>");
+
+                let reason = match feature.step {
+                    Some(step) => format!("{} failed: {}", step, messages),
+                    None => messages,
+                };
+
+                writeln!(
+                    markdown,
+                    "| {} | {} | {} | {} | {:.1}s | {} |",
+                    escape_markdown(&dependency.package),
+                    escape_markdown(&dependency.dependency),
+                    escape_markdown(&feature.name),
+                    feature.verdict,
+                    feature.duration,
+                    reason
+                )?;
+            }
+        }
+
+        Ok(markdown)
+    }
+
+    /// kept features pass, features which could be disabled fail and the rest is skipped
+    fn to_junit(&self) -> Result<String> {
+        let mut xml = String::new();
+
+        writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            xml,
+            r#"<testsuites name="cargo features prune" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+            self.summary.tested,
+            self.summary.disabled,
+            self.summary.known_false_positive + self.summary.inconclusive,
+            self.duration
+        )?;
+
+        for dependency in &self.dependencies {
+            let count = |verdicts: &[Verdict]| {
+                dependency
+                    .features
+                    .iter()
+                    .filter(|feature| verdicts.contains(&feature.verdict))
+                    .count()
+            };
+
+            writeln!(
+                xml,
+                r#"  <testsuite name="{}/{}" tests="{}" failures="{}" skipped="{}" time="{:.3}">"#,
+                escape_xml(&dependency.package),
+                escape_xml(&dependency.dependency),
+                dependency.features.len(),
+                count(&[Verdict::Disabled]),
+                count(&[Verdict::KnownFalsePositive, Verdict::Inconclusive]),
+                dependency.duration
+            )?;
+
+            for feature in &dependency.features {
+                write!(
+                    xml,
+                    r#"    <testcase name="{}" classname="{}.{}" time="{:.3}">"#,
+                    escape_xml(&feature.name),
+                    escape_xml(&dependency.package),
+                    escape_xml(&dependency.dependency),
+                    feature.duration
+                )?;
+
+                match feature.verdict {
+                    Verdict::Disabled => write!(
+                        xml,
+                        r#"<failure message="feature is not needed and can be disabled"/>"#
+                    )?,
+                    Verdict::KnownFalsePositive => {
+                        write!(xml, r#"<skipped message="known false positive"/>"#)?
+                    }
+                    Verdict::Inconclusive => write!(
+                        xml,
+                        r#"<skipped message="inconclusive - the check timed out"/>"#
+                    )?,
+                    Verdict::Kept => {
+                        if !feature.messages.is_empty() {
+                            write!(
+                                xml,
+                                "<system-out>{}</system-out>",
+                                escape_xml(&feature.messages.join("\n"))
+                            )?
+                        }
+                    }
+                }
+
+                writeln!(xml, "</testcase>")?;
+            }
+
+            writeln!(xml, "  </testsuite>")?;
+        }
+
+        writeln!(xml, "</testsuites>")?;
+
+        Ok(xml)
+    }
+}
+
+fn get_duration(seconds: f64) -> Result<Duration> {
+    Duration::try_from_secs_f64(seconds).map_err(|_| eyre!("{} is not a valid duration", seconds))
+}
+
+fn get_features<'a>(features: &'a FeaturesMap, job: &Job) -> &'a [FeatureName] {
+    features
+        .get(&job.package_name)
+        .and_then(|dependencies| dependencies.get(&job.dependency_name))
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\n', " ")
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
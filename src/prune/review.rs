@@ -0,0 +1,284 @@
+use crate::project::document::Document;
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName};
+use color_eyre::Result;
+use console::{style, Key, Term};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::io::Write;
+use std::ops::Range;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Decision {
+    Accept,
+    Reject,
+    Keep,
+}
+
+pub struct ReviewEntry {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    pub feature: FeatureName,
+    pub decision: Decision,
+}
+
+enum Line {
+    Dependency(String),
+    Feature(usize),
+}
+
+/// lets the user decide for every proposed disablement if it gets applied, rejected or kept for good
+pub struct Review<'a> {
+    term: Term,
+
+    document: &'a Document,
+
+    entries: Vec<ReviewEntry>,
+    lines: Vec<Line>,
+
+    selected_index: usize,
+}
+
+impl<'a> Review<'a> {
+    pub fn new(document: &'a Document, to_be_disabled: &FeaturesMap) -> Review<'a> {
+        let mut entries = vec![];
+        let mut lines = vec![];
+
+        for (package_name, dependencies) in to_be_disabled
+            .iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            for (dependency_name, features) in dependencies
+                .iter()
+                .filter(|(_, features)| !features.is_empty())
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+            {
+                if document.is_workspace() {
+                    lines.push(Line::Dependency(format!(
+                        "{}/{}",
+                        package_name, dependency_name
+                    )));
+                } else {
+                    lines.push(Line::Dependency(dependency_name.to_string()));
+                }
+
+                for feature in features.iter().sorted() {
+                    lines.push(Line::Feature(entries.len()));
+
+                    entries.push(ReviewEntry {
+                        package_name: package_name.to_string(),
+                        dependency_name: dependency_name.to_string(),
+                        feature: feature.to_string(),
+                        decision: Decision::Accept,
+                    });
+                }
+            }
+        }
+
+        Review {
+            term: Term::buffered_stdout(),
+            document,
+            entries,
+            lines,
+            selected_index: 0,
+        }
+    }
+
+    /// returns `None` if the review got canceled
+    pub fn start(mut self) -> Result<Option<Vec<ReviewEntry>>> {
+        if self.entries.is_empty() {
+            return Ok(Some(self.entries));
+        }
+
+        //setup
+        self.term.hide_cursor()?;
+
+        for _ in 1..self.term.size().0 {
+            writeln!(self.term)?;
+        }
+
+        self.term.move_cursor_to(0, 0)?;
+        self.term.flush()?;
+
+        let is_finished = loop {
+            self.display()?;
+
+            self.term.flush()?;
+
+            //clear previous screen
+            self.term.clear_last_lines(self.term.size().0 as usize)?;
+
+            match self.input_event()? {
+                RunningState::Running => {}
+                RunningState::Finished => break true,
+                RunningState::Canceled => break false,
+            }
+        };
+
+        self.term.show_cursor()?;
+        self.term.flush()?;
+
+        Ok(is_finished.then_some(self.entries))
+    }
+
+    fn display(&mut self) -> Result<()> {
+        write!(
+            self.term,
+            "Review - {} accept {} reject {} keep {} apply {} cancel",
+            style("a").bold(),
+            style("r").bold(),
+            style("k").bold(),
+            style("enter").bold(),
+            style("esc").bold()
+        )?;
+
+        let range = self.get_max_range();
+
+        for (line_index, line) in (1..).zip(&self.lines[range]) {
+            match line {
+                Line::Dependency(name) => {
+                    self.term.move_cursor_to(2, line_index)?;
+                    write!(self.term, "{}", name)?;
+                }
+                Line::Feature(index) => {
+                    let entry = &self.entries[*index];
+
+                    if *index == self.selected_index {
+                        self.term.move_cursor_to(0, line_index)?;
+                        write!(self.term, ">")?;
+                    }
+
+                    self.term.move_cursor_to(4, line_index)?;
+
+                    let marker = match entry.decision {
+                        Decision::Accept => style("[-]").red(),
+                        Decision::Reject => style("[X]"),
+                        Decision::Keep => style("[K]").green(),
+                    };
+
+                    write!(self.term, "{} {}", marker, entry.feature)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn input_event(&mut self) -> Result<RunningState> {
+        match self.term.read_key()? {
+            Key::ArrowUp => {
+                self.selected_index =
+                    (self.selected_index + self.entries.len() - 1) % self.entries.len();
+            }
+            Key::ArrowDown => {
+                self.selected_index = (self.selected_index + 1) % self.entries.len();
+            }
+            Key::Char(' ') => {
+                let decision = match self.entries[self.selected_index].decision {
+                    Decision::Accept => Decision::Reject,
+                    Decision::Reject => Decision::Keep,
+                    Decision::Keep => Decision::Accept,
+                };
+
+                self.decide(self.selected_index, decision)?;
+            }
+            Key::Char('a') => self.decide(self.selected_index, Decision::Accept)?,
+            Key::Char('r') => self.decide(self.selected_index, Decision::Reject)?,
+            Key::Char('k') => self.decide(self.selected_index, Decision::Keep)?,
+            Key::Enter => return Ok(RunningState::Finished),
+            Key::Escape => return Ok(RunningState::Canceled),
+            _ => {}
+        }
+
+        Ok(RunningState::Running)
+    }
+
+    /// features depend on each other - disabling a feature also disables the features requiring it,
+    /// keeping a feature also keeps the features it requires
+    fn decide(&mut self, index: usize, decision: Decision) -> Result<()> {
+        if self.entries[index].decision == decision {
+            return Ok(());
+        }
+
+        self.entries[index].decision = decision;
+
+        let entry = &self.entries[index];
+
+        let dependency = self
+            .document
+            .get_package(&entry.package_name)?
+            .get_dep(&entry.dependency_name)?;
+
+        let related_features = match decision {
+            Decision::Accept => dependency.get_dependent_features(&entry.feature),
+            Decision::Reject | Decision::Keep => dependency
+                .get_feature(&entry.feature)
+                .map(|data| {
+                    data.sub_features
+                        .iter()
+                        .map(|sub_feature| sub_feature.name.to_string())
+                        .collect_vec()
+                })
+                .unwrap_or_default(),
+        };
+
+        let related_indices = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, related)| {
+                related.package_name == entry.package_name
+                    && related.dependency_name == entry.dependency_name
+                    && related_features.contains(&related.feature)
+            })
+            .map(|(index, _)| index)
+            .collect_vec();
+
+        for index in related_indices {
+            self.decide(index, decision)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_max_range(&self) -> Range<usize> {
+        let current_selected = self
+            .lines
+            .iter()
+            .position(|line| matches!(line, Line::Feature(index) if *index == self.selected_index))
+            .unwrap_or_default() as isize;
+
+        let max_range = self.lines.len();
+
+        let height = self.term.size().0 as usize;
+
+        let start = (current_selected - height as isize / 2 + 1)
+            .min(max_range as isize - height as isize + 1)
+            .max(0) as usize;
+
+        start..max_range.min(start + height - 1)
+    }
+}
+
+enum RunningState {
+    Running,
+    Finished,
+    Canceled,
+}
+
+/// groups the kept features by their package and dependency
+pub fn get_kept_features(
+    entries: &[ReviewEntry],
+) -> HashMap<(&PackageName, &DependencyName), Vec<&FeatureName>> {
+    let mut kept: HashMap<_, Vec<_>> = HashMap::new();
+
+    for entry in entries
+        .iter()
+        .filter(|entry| entry.decision == Decision::Keep)
+    {
+        kept.entry((&entry.package_name, &entry.dependency_name))
+            .or_default()
+            .push(&entry.feature);
+    }
+
+    kept
+}
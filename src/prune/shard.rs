@@ -0,0 +1,119 @@
+use crate::prune::FeaturesMap;
+use crate::Shard;
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::ops::Not;
+
+/// keeps the dependencies of the shard - dependencies are never split, so all features of one are tested together.
+/// they are dealt out one after another in the order they are tested, so every shard gets a similar share
+pub fn get_shard(features: FeaturesMap, shard: &Shard) -> FeaturesMap {
+    let mut shard_features: FeaturesMap = HashMap::new();
+
+    let dependencies = features
+        .into_iter()
+        .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        .flat_map(|(package_name, dependencies)| {
+            dependencies
+                .into_iter()
+                .filter(|(_, features)| features.is_empty().not())
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                .map(move |(dependency_name, features)| {
+                    (package_name.clone(), dependency_name, features)
+                })
+        });
+
+    for (index, (package_name, dependency_name, features)) in dependencies.enumerate() {
+        if index % shard.count == shard.index - 1 {
+            shard_features
+                .entry(package_name)
+                .or_default()
+                .insert(dependency_name, features);
+        }
+    }
+
+    shard_features
+}
+
+#[cfg(test)]
+mod tests {
+    use super::get_shard;
+    use crate::prune::FeaturesMap;
+    use crate::Shard;
+    use std::collections::HashMap;
+
+    fn features() -> FeaturesMap {
+        HashMap::from([
+            (
+                "app".to_string(),
+                HashMap::from([
+                    ("serde".to_string(), vec!["std".to_string()]),
+                    (
+                        "tokio".to_string(),
+                        vec!["rt".to_string(), "net".to_string()],
+                    ),
+                    ("empty".to_string(), vec![]),
+                ]),
+            ),
+            (
+                "lib".to_string(),
+                HashMap::from([
+                    ("log".to_string(), vec!["std".to_string()]),
+                    ("regex".to_string(), vec!["unicode".to_string()]),
+                ]),
+            ),
+        ])
+    }
+
+    fn dependencies(features: &FeaturesMap) -> Vec<(String, String, Vec<String>)> {
+        let mut dependencies = features
+            .iter()
+            .flat_map(|(package_name, dependencies)| {
+                dependencies.iter().map(|(dependency_name, features)| {
+                    (
+                        package_name.clone(),
+                        dependency_name.clone(),
+                        features.clone(),
+                    )
+                })
+            })
+            .collect::<Vec<_>>();
+
+        dependencies.sort();
+        dependencies
+    }
+
+    #[test]
+    fn shards_split_dependencies_without_overlap() {
+        let shards = (1..=3)
+            .map(|index| get_shard(features(), &Shard { index, count: 3 }))
+            .collect::<Vec<_>>();
+
+        let mut combined = shards.iter().flat_map(dependencies).collect::<Vec<_>>();
+        combined.sort();
+
+        let mut expected = dependencies(&features());
+        expected.retain(|(_, _, features)| !features.is_empty());
+
+        assert_eq!(combined, expected);
+
+        for shard in &shards {
+            let count = dependencies(shard).len();
+            assert!((1..=2).contains(&count), "unbalanced shard {:?}", shard);
+        }
+    }
+
+    #[test]
+    fn single_shard_keeps_every_dependency_with_features() {
+        let shard = get_shard(features(), &Shard { index: 1, count: 1 });
+
+        assert_eq!(dependencies(&shard).len(), 4);
+        assert!(!shard["app"].contains_key("empty"));
+    }
+
+    #[test]
+    fn surplus_shards_are_empty() {
+        let shard = get_shard(features(), &Shard { index: 5, count: 5 });
+
+        assert!(shard.is_empty());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::project::document::Document;
+use crate::prune::worker::{DependencyResult, Job};
+use crate::prune::{get_data_dir, DependencyName, FeatureName, PackageName};
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// results of already tested dependencies, so an interrupted prune can be resumed
+#[derive(Serialize, Deserialize)]
+pub struct PruneState {
+    /// hash of the lock file, all manifests and the check settings - results of a different project state are useless
+    key: String,
+    dependencies: Vec<SavedDependency>,
+
+    #[serde(skip)]
+    path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedDependency {
+    package_name: PackageName,
+    dependency_name: DependencyName,
+    features: Vec<FeatureName>,
+    result: DependencyResult,
+}
+
+impl PruneState {
+    pub fn new(document: &Document, settings: &str) -> Result<PruneState> {
+        let mut content = settings.to_string();
+
+        if let Ok(lock_file) = fs::read_to_string(document.root_path().join("Cargo.lock")) {
+            content.push_str(&lock_file);
+        }
+
+        for package in document.get_packages() {
+            content.push_str(&fs::read_to_string(&package.manifest_path)?);
+        }
+
+        Ok(PruneState {
+            key: format!("{:016x}", fnv_hash(content.as_bytes())),
+            dependencies: vec![],
+            path: get_data_dir(document.root_path()).join("prune-state.json"),
+        })
+    }
+
+    /// loads the saved results, returns false if they do not belong to the current project state
+    pub fn load(&mut self) -> Result<bool> {
+        let Ok(content) = fs::read_to_string(&self.path) else {
+            return Ok(false);
+        };
+
+        let Ok(saved) = serde_json::from_str::<PruneState>(&content) else {
+            return Ok(false);
+        };
+
+        if saved.key != self.key {
+            return Ok(false);
+        }
+
+        self.dependencies = saved.dependencies;
+
+        Ok(true)
+    }
+
+    pub fn get_result(&self, job: &Job) -> Option<&DependencyResult> {
+        self.dependencies
+            .iter()
+            .find(|saved| {
+                saved.package_name == job.package_name
+                    && saved.dependency_name == job.dependency_name
+                    && saved
+                        .features
+                        .iter()
+                        .sorted()
+                        .eq(job.features.iter().sorted())
+            })
+            .map(|saved| &saved.result)
+    }
+
+    pub fn add_result(&mut self, job: &Job, result: &DependencyResult) -> Result<()> {
+        self.insert_result(job, result);
+        self.save()
+    }
+
+    /// adds the result without saving it, e.g. for results which are already saved somewhere else
+    pub fn insert_result(&mut self, job: &Job, result: &DependencyResult) {
+        self.dependencies.push(SavedDependency {
+            package_name: job.package_name.to_string(),
+            dependency_name: job.dependency_name.to_string(),
+            features: job.features.clone(),
+            result: result.clone(),
+        });
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&self.path, serde_json::to_string(self)?)?;
+
+        Ok(())
+    }
+
+    pub fn remove(&self) -> Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// FNV-1a - stable across rust versions unlike the std hasher
+fn fnv_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
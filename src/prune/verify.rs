@@ -0,0 +1,144 @@
+use crate::io::save::save_dependency;
+use crate::project::document::Document;
+use crate::prune::check::Checker;
+use crate::prune::display::Display;
+use crate::prune::{DependencyName, FeatureName, FeaturesMap, PackageName};
+use color_eyre::Result;
+use itertools::Itertools;
+use std::collections::HashMap;
+
+type Disablement = (PackageName, DependencyName, FeatureName);
+
+/// features are only tested in isolation, so make sure disabling all of them at once still works.
+/// if not, restore a minimal set of features that makes the project compile again
+pub fn verify_combined(
+    document: &mut Document,
+    display: &mut Display,
+    checker: &Checker,
+    to_be_disabled: FeaturesMap,
+) -> Result<FeaturesMap> {
+    let disablements = to_be_disabled
+        .iter()
+        .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        .flat_map(|(package_name, dependencies)| {
+            dependencies
+                .iter()
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                .flat_map(move |(dependency_name, features)| {
+                    features.iter().map(move |feature| {
+                        (
+                            package_name.to_string(),
+                            dependency_name.to_string(),
+                            feature.to_string(),
+                        )
+                    })
+                })
+        })
+        .collect_vec();
+
+    if disablements.is_empty() {
+        return Ok(to_be_disabled);
+    }
+
+    display.start_verification()?;
+
+    if apply(document, &disablements, &disablements, checker)? {
+        display.finish_verification(&[])?;
+        return collect_disabled(document, &disablements);
+    }
+
+    // most conflicts are between two features, so restoring a single one is usually enough
+    for (index, restored) in disablements.iter().enumerate() {
+        let mut remaining = disablements.clone();
+        remaining.remove(index);
+
+        if apply(document, &disablements, &remaining, checker)? {
+            display.finish_verification(&[restored])?;
+            return collect_disabled(document, &disablements);
+        }
+    }
+
+    // otherwise add one disablement at a time and keep only the ones that still compile
+    let mut accepted = vec![];
+    let mut restored = vec![];
+    let mut is_applied = true;
+
+    for disablement in &disablements {
+        accepted.push(disablement.clone());
+
+        is_applied = apply(document, &disablements, &accepted, checker)?;
+
+        if !is_applied {
+            accepted.pop();
+            restored.push(disablement);
+        }
+    }
+
+    // the document still holds the last rejected attempt
+    if !is_applied && !apply(document, &disablements, &accepted, checker)? {
+        // the accepted set compiled before, so the checks are not reliable - keep every feature
+        apply(document, &disablements, &[], checker)?;
+        restored = disablements.iter().collect();
+    }
+
+    display.finish_verification(&restored)?;
+    collect_disabled(document, &disablements)
+}
+
+/// resets all features of `all` and only disables `disabled`, returns if the project still compiles
+fn apply(
+    document: &mut Document,
+    all: &[Disablement],
+    disabled: &[Disablement],
+    checker: &Checker,
+) -> Result<bool> {
+    for (package_name, dependency_name, feature) in all {
+        document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?
+            .enable_feature(feature)?;
+    }
+
+    for (package_name, dependency_name, feature) in disabled {
+        document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?
+            .disable_feature(feature)?;
+    }
+
+    for (package_name, dependency_name) in all
+        .iter()
+        .map(|(package_name, dependency_name, _)| (package_name, dependency_name))
+        .dedup()
+    {
+        save_dependency(document, package_name, dependency_name)?;
+    }
+
+    Ok(checker.check(document.root_path())?.is_passed())
+}
+
+/// restoring a feature might also enable some of its sub features, so read the actual state back
+fn collect_disabled(document: &Document, disablements: &[Disablement]) -> Result<FeaturesMap> {
+    let mut features_map: FeaturesMap = HashMap::new();
+
+    for (package_name, dependency_name, feature) in disablements {
+        let dependency = document
+            .get_package(package_name)?
+            .get_dep(dependency_name)?;
+
+        let features = features_map
+            .entry(package_name.to_string())
+            .or_default()
+            .entry(dependency_name.to_string())
+            .or_default();
+
+        if dependency
+            .get_feature(feature)
+            .is_some_and(|data| !data.is_enabled())
+        {
+            features.push(feature.to_string());
+        }
+    }
+
+    Ok(features_map)
+}
@@ -0,0 +1,556 @@
+use crate::io::save::save_dependency;
+use crate::project::document::Document;
+use crate::prune::check::{CheckResult, CheckStep, Checker, CompileResult, Failure};
+use crate::prune::{
+    set_features_to_be_disabled, set_features_to_be_kept, DependencyName, FeatureName, PackageName,
+};
+use crate::{CleanLevel, PruneArgs, PruneStrategy};
+use color_eyre::eyre::eyre;
+use color_eyre::Result;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::mpsc::Sender;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[derive(Clone)]
+pub struct Job {
+    pub package_name: PackageName,
+    pub dependency_name: DependencyName,
+    pub features: Vec<FeatureName>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DependencyResult {
+    pub to_be_disabled: Vec<FeatureName>,
+    pub known_features: Vec<FeatureName>,
+    pub check_count: usize,
+    /// the amount of checks the linear strategy would need
+    pub candidate_count: usize,
+    /// why the features which have to be kept failed
+    #[serde(default)]
+    pub failures: HashMap<FeatureName, Failure>,
+    /// features whose check timed out, they are kept to be safe
+    #[serde(default)]
+    pub inconclusive: Vec<FeatureName>,
+    /// time spent on the checks of each feature, checks of multiple features are split evenly
+    #[serde(default)]
+    pub durations: HashMap<FeatureName, Duration>,
+    #[serde(default)]
+    pub duration: Duration,
+}
+
+/// shown while `--try-no-default` checks all default features at once
+pub const NO_DEFAULT_FEATURES: &str = "default-features = false";
+
+pub enum Event {
+    DependencyStarted {
+        worker: usize,
+        job: usize,
+    },
+    FeatureStarted {
+        worker: usize,
+        id: usize,
+        feature: FeatureName,
+    },
+    FeaturesFinished {
+        worker: usize,
+        count: usize,
+    },
+    /// the try of `default-features = false`, it is not one of the counted features
+    DefaultsFinished {
+        worker: usize,
+        is_disabled: bool,
+        duration: Duration,
+    },
+    DependencyFinished {
+        worker: usize,
+        job: usize,
+        result: DependencyResult,
+    },
+}
+
+/// each worker owns its own copy of the project and takes dependencies from the queue until it is empty
+pub struct Worker<'a> {
+    pub id: usize,
+    pub document: &'a mut Document,
+    pub queue: &'a Mutex<VecDeque<(usize, Job)>>,
+    pub sender: Sender<Event>,
+    pub args: &'a PruneArgs,
+    pub checker: &'a Checker,
+    pub known_features: &'a HashMap<String, Vec<String>>,
+}
+
+impl Worker<'_> {
+    pub fn run(mut self) -> Result<()> {
+        let result = self.run_jobs();
+
+        if result.is_err() {
+            // stop the other workers as the result would be incomplete anyway
+            self.queue
+                .lock()
+                .map_err(|_| eyre!("could not lock job queue"))?
+                .clear();
+        }
+
+        result
+    }
+
+    fn run_jobs(&mut self) -> Result<()> {
+        let mut last_package: Option<PackageName> = None;
+
+        loop {
+            let Some((job_id, job)) = self
+                .queue
+                .lock()
+                .map_err(|_| eyre!("could not lock job queue"))?
+                .pop_front()
+            else {
+                return Ok(());
+            };
+
+            if let CleanLevel::Package = self.args.clean {
+                if last_package
+                    .as_ref()
+                    .is_some_and(|name| *name != job.package_name)
+                {
+                    self.checker.clean(self.document.root_path())?;
+                }
+            }
+            last_package = Some(job.package_name.clone());
+
+            self.send(Event::DependencyStarted {
+                worker: self.id,
+                job: job_id,
+            })?;
+
+            let result = self.test_dependency(&job)?;
+
+            self.send(Event::DependencyFinished {
+                worker: self.id,
+                job: job_id,
+                result,
+            })?;
+
+            if let CleanLevel::Dependency = self.args.clean {
+                self.checker.clean(self.document.root_path())?;
+            }
+        }
+    }
+
+    fn test_dependency(&mut self, job: &Job) -> Result<DependencyResult> {
+        let Job {
+            package_name,
+            dependency_name,
+            features,
+        } = job;
+
+        let mut known_features_list = vec![];
+        let dependency = self
+            .document
+            .get_package(package_name)?
+            .get_dep(dependency_name)?;
+
+        for feature_name in self.known_features.get(dependency_name).unwrap_or(&vec![]) {
+            set_features_to_be_kept(
+                dependency,
+                feature_name.to_string(),
+                &mut known_features_list,
+            )
+        }
+
+        let mut to_be_disabled = vec![];
+        to_be_disabled.append(&mut known_features_list.clone());
+
+        let candidate_count = features
+            .iter()
+            .filter(|feature| !to_be_disabled.contains(feature))
+            .count();
+
+        let start = Instant::now();
+
+        let mut result = DependencyResult {
+            to_be_disabled,
+            known_features: known_features_list,
+            check_count: 0,
+            candidate_count,
+            failures: HashMap::new(),
+            inconclusive: vec![],
+            durations: HashMap::new(),
+            duration: Duration::ZERO,
+        };
+
+        if self.args.try_no_default {
+            result.check_count += self.test_without_defaults(job, &mut result)?;
+        }
+
+        result.check_count += match self.args.strategy {
+            PruneStrategy::Linear => self.test_linear(job, &mut result)?,
+            PruneStrategy::Bisect => self.test_bisect(job, &mut result)?,
+            PruneStrategy::Minimize => self.test_minimize(job, &mut result)?,
+        };
+
+        // disabling a feature also disables the features enabling it, which might have timed out before
+        result
+            .inconclusive
+            .retain(|feature| !result.to_be_disabled.contains(feature));
+
+        result.duration = start.elapsed();
+
+        Ok(result)
+    }
+
+    /// disables all default features at once - like `default-features = false` - before they get tested one by one
+    fn test_without_defaults(&mut self, job: &Job, result: &mut DependencyResult) -> Result<usize> {
+        let dependency = self
+            .document
+            .get_package(&job.package_name)?
+            .get_dep(&job.dependency_name)?;
+
+        if !dependency.can_use_default() {
+            return Ok(0);
+        }
+
+        let default_features = job
+            .features
+            .iter()
+            .filter(|feature| !result.to_be_disabled.contains(feature))
+            .filter(|feature| {
+                dependency
+                    .get_feature(feature)
+                    .is_some_and(|data| data.is_default)
+            })
+            .cloned()
+            .collect_vec();
+
+        if default_features.is_empty() {
+            return Ok(0);
+        }
+
+        self.send(Event::FeatureStarted {
+            worker: self.id,
+            id: 0,
+            feature: NO_DEFAULT_FEATURES.to_string(),
+        })?;
+
+        let start = Instant::now();
+        let is_disabled = self
+            .check_disabled(job, &default_features, result)?
+            .is_passed();
+
+        self.send(Event::DefaultsFinished {
+            worker: self.id,
+            is_disabled,
+            duration: start.elapsed(),
+        })?;
+
+        if is_disabled {
+            for feature in default_features {
+                set_features_to_be_disabled(
+                    self.document
+                        .get_package(&job.package_name)?
+                        .get_dep(&job.dependency_name)?,
+                    feature,
+                    &mut result.to_be_disabled,
+                );
+            }
+        }
+
+        Ok(1)
+    }
+
+    /// disables one feature after another
+    fn test_linear(&mut self, job: &Job, result: &mut DependencyResult) -> Result<usize> {
+        let mut check_count = 0;
+
+        for (id, feature) in job.features.iter().enumerate() {
+            self.send(Event::FeatureStarted {
+                worker: self.id,
+                id,
+                feature: feature.to_string(),
+            })?;
+
+            if !result.to_be_disabled.contains(feature) {
+                check_count += 1;
+
+                match self.check_disabled(job, std::slice::from_ref(feature), result)? {
+                    CheckResult::Passed => set_features_to_be_disabled(
+                        self.document
+                            .get_package(&job.package_name)?
+                            .get_dep(&job.dependency_name)?,
+                        feature.to_string(),
+                        &mut result.to_be_disabled,
+                    ),
+                    CheckResult::Failed(failure) => {
+                        result.failures.insert(feature.to_string(), failure);
+                    }
+                    CheckResult::TimedOut => result.inconclusive.push(feature.to_string()),
+                }
+            }
+
+            self.send(Event::FeaturesFinished {
+                worker: self.id,
+                count: 1,
+            })?;
+        }
+
+        Ok(check_count)
+    }
+
+    /// disables a batch of features at once and only splits it in half if the check fails
+    fn test_bisect(&mut self, job: &Job, result: &mut DependencyResult) -> Result<usize> {
+        let mut finished_count = 0;
+
+        let (candidates, skipped): (Vec<_>, Vec<_>) = job
+            .features
+            .iter()
+            .cloned()
+            .partition(|feature| !result.to_be_disabled.contains(feature));
+
+        self.finish_features(skipped.len(), &mut finished_count)?;
+
+        self.bisect(job, candidates, result, &mut finished_count)
+    }
+
+    fn bisect(
+        &mut self,
+        job: &Job,
+        candidates: Vec<FeatureName>,
+        result: &mut DependencyResult,
+        finished_count: &mut usize,
+    ) -> Result<usize> {
+        let mut check_count = 0;
+
+        let mut batches = vec![candidates];
+
+        while let Some(batch) = batches.pop() {
+            // features might already be disabled because a feature they require was disabled
+            let (batch, skipped): (Vec<_>, Vec<_>) = batch
+                .into_iter()
+                .partition(|feature| !result.to_be_disabled.contains(feature));
+
+            self.finish_features(skipped.len(), finished_count)?;
+
+            if batch.is_empty() {
+                continue;
+            }
+
+            self.send(Event::FeatureStarted {
+                worker: self.id,
+                id: *finished_count,
+                feature: batch.join(", "),
+            })?;
+
+            check_count += 1;
+
+            match (self.check_disabled(job, &batch, result)?, batch.as_slice()) {
+                (CheckResult::Passed, _) => {
+                    for feature in &batch {
+                        set_features_to_be_disabled(
+                            self.document
+                                .get_package(&job.package_name)?
+                                .get_dep(&job.dependency_name)?,
+                            feature.to_string(),
+                            &mut result.to_be_disabled,
+                        );
+                    }
+
+                    self.finish_features(batch.len(), finished_count)?;
+                }
+                (CheckResult::Failed(failure), [feature]) => {
+                    result.failures.insert(feature.to_string(), failure);
+                    self.finish_features(1, finished_count)?;
+                }
+                (CheckResult::TimedOut, [feature]) => {
+                    result.inconclusive.push(feature.to_string());
+                    self.finish_features(1, finished_count)?;
+                }
+                _ => {
+                    let (first, second) = batch.split_at(batch.len() / 2);
+
+                    batches.push(second.to_vec());
+                    batches.push(first.to_vec());
+                }
+            }
+        }
+
+        Ok(check_count)
+    }
+
+    /// disables all features at once and enables only the ones whose items the compiler misses, until it compiles.
+    /// the remaining features are checked together and bisected if that fails
+    fn test_minimize(&mut self, job: &Job, result: &mut DependencyResult) -> Result<usize> {
+        let mut check_count = 0;
+        let mut finished_count = 0;
+
+        let (mut candidates, skipped): (Vec<_>, Vec<_>) = job
+            .features
+            .iter()
+            .cloned()
+            .partition(|feature| !result.to_be_disabled.contains(feature));
+
+        self.finish_features(skipped.len(), &mut finished_count)?;
+
+        let is_compiling = loop {
+            if candidates.is_empty() {
+                return Ok(check_count);
+            }
+
+            self.send(Event::FeatureStarted {
+                worker: self.id,
+                id: finished_count,
+                feature: candidates.join(", "),
+            })?;
+
+            check_count += 1;
+
+            let errors = match self.with_disabled(job, &candidates, result, |checker, path| {
+                checker.check_compile(path)
+            })? {
+                CompileResult::Passed => break true,
+                CompileResult::TimedOut => break false,
+                CompileResult::Failed(errors) => errors,
+            };
+
+            let dependency = self
+                .document
+                .get_package(&job.package_name)?
+                .get_dep(&job.dependency_name)?;
+
+            let mut required = vec![];
+
+            for (features, message) in &errors {
+                for feature in features
+                    .iter()
+                    .filter(|feature| candidates.contains(feature))
+                {
+                    result
+                        .failures
+                        .entry(feature.to_string())
+                        .or_insert_with(|| Failure {
+                            step: CheckStep::Build,
+                            messages: vec![],
+                            targets: vec![],
+                            combinations: vec![],
+                        })
+                        .messages
+                        .push(message.to_string());
+
+                    // the features it requires have to stay enabled as well
+                    set_features_to_be_kept(dependency, feature.to_string(), &mut required);
+                }
+            }
+
+            // the errors do not point to a feature - find the needed ones the slow way
+            if required.is_empty() {
+                break false;
+            }
+
+            let kept_count = candidates
+                .iter()
+                .filter(|feature| required.contains(feature))
+                .count();
+
+            candidates.retain(|feature| !required.contains(feature));
+            self.finish_features(kept_count, &mut finished_count)?;
+        };
+
+        if is_compiling {
+            check_count += 1;
+        }
+
+        if is_compiling && self.check_disabled(job, &candidates, result)?.is_passed() {
+            for feature in &candidates {
+                set_features_to_be_disabled(
+                    self.document
+                        .get_package(&job.package_name)?
+                        .get_dep(&job.dependency_name)?,
+                    feature.to_string(),
+                    &mut result.to_be_disabled,
+                );
+            }
+
+            self.finish_features(candidates.len(), &mut finished_count)?;
+
+            return Ok(check_count);
+        }
+
+        Ok(check_count + self.bisect(job, candidates, result, &mut finished_count)?)
+    }
+
+    /// disables the features, checks if the project still compiles and resets the dependency
+    fn check_disabled(
+        &mut self,
+        job: &Job,
+        features: &[FeatureName],
+        result: &mut DependencyResult,
+    ) -> Result<CheckResult> {
+        self.with_disabled(job, features, result, |checker, path| checker.check(path))
+    }
+
+    /// runs `check` while the features are disabled and resets the dependency afterward
+    fn with_disabled<T>(
+        &mut self,
+        job: &Job,
+        features: &[FeatureName],
+        result: &mut DependencyResult,
+        check: impl FnOnce(&Checker, &Path) -> Result<T>,
+    ) -> Result<T> {
+        let Job {
+            package_name,
+            dependency_name,
+            features: all_features,
+        } = job;
+
+        for feature in features {
+            self.document
+                .get_package_mut(package_name)?
+                .get_dep_mut(dependency_name)?
+                .disable_feature(feature)?;
+        }
+
+        save_dependency(self.document, package_name, dependency_name)?;
+
+        let start = Instant::now();
+        let check_result = check(self.checker, self.document.root_path())?;
+
+        let duration = start.elapsed() / features.len() as u32;
+
+        for feature in features {
+            *result.durations.entry(feature.to_string()).or_default() += duration;
+        }
+
+        //reset to start
+        for feature in all_features {
+            self.document
+                .get_package_mut(package_name)?
+                .get_dep_mut(dependency_name)?
+                .enable_feature(feature)?;
+        }
+
+        save_dependency(self.document, package_name, dependency_name)?;
+
+        Ok(check_result)
+    }
+
+    fn finish_features(&self, count: usize, finished_count: &mut usize) -> Result<()> {
+        if count == 0 {
+            return Ok(());
+        }
+
+        *finished_count += count;
+
+        self.send(Event::FeaturesFinished {
+            worker: self.id,
+            count,
+        })
+    }
+
+    fn send(&self, event: Event) -> Result<()> {
+        self.sender
+            .send(event)
+            .map_err(|_| eyre!("could not report progress"))
+    }
+}
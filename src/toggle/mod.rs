@@ -0,0 +1,72 @@
+use crate::io::save::save_dependency;
+use crate::project::dependency::feature::EnabledState;
+use crate::project::document::Document;
+use crate::toggle::spec::FeatureSpec;
+use color_eyre::eyre::{bail, ContextCompat};
+use color_eyre::Result;
+
+mod spec;
+
+pub fn enable(specs: Vec<String>) -> Result<()> {
+    set_features(specs, true)
+}
+
+pub fn disable(specs: Vec<String>) -> Result<()> {
+    set_features(specs, false)
+}
+
+fn set_features(specs: Vec<String>, enable: bool) -> Result<()> {
+    let mut document = Document::new(".")?;
+
+    // resolve everything first so that a typo does not leave a half applied change
+    let specs = specs
+        .iter()
+        .map(|spec| FeatureSpec::parse(spec, &document))
+        .collect::<Result<Vec<FeatureSpec>>>()?;
+
+    for spec in &specs {
+        let dependency = document
+            .get_package(&spec.package_name)?
+            .get_dep(&spec.dependency_name)?;
+
+        let data = dependency
+            .get_feature(&spec.feature_name)
+            .context(format!("could not find {}", spec.feature_name))?;
+
+        if data.enabled_state == EnabledState::Workspace {
+            bail!(
+                "{} of {} is enabled by the workspace dependency - change it there instead",
+                spec.feature_name,
+                dependency.get_key()
+            )
+        }
+    }
+
+    for spec in &specs {
+        let dependency = document
+            .get_package_mut(&spec.package_name)?
+            .get_dep_mut(&spec.dependency_name)?;
+
+        if enable {
+            dependency.enable_feature(&spec.feature_name)?;
+        } else {
+            dependency.disable_feature(&spec.feature_name)?;
+        }
+    }
+
+    let mut changed_dependencies = vec![];
+
+    for spec in &specs {
+        let entry = (&spec.package_name, &spec.dependency_name);
+
+        if !changed_dependencies.contains(&entry) {
+            changed_dependencies.push(entry);
+        }
+    }
+
+    for (package_name, dependency_name) in changed_dependencies {
+        save_dependency(&mut document, package_name, dependency_name)?;
+    }
+
+    Ok(())
+}
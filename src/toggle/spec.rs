@@ -0,0 +1,58 @@
+use crate::project::document::Document;
+use color_eyre::eyre::{bail, ContextCompat};
+use color_eyre::Result;
+
+/// a feature referenced as `<package>/<dependency>/<feature>`
+pub struct FeatureSpec {
+    pub package_name: String,
+    pub dependency_name: String,
+    pub feature_name: String,
+}
+
+impl FeatureSpec {
+    /// resolves the spec against the document, the package can be omitted if there is only one
+    pub fn parse(spec: &str, document: &Document) -> Result<FeatureSpec> {
+        let parts = spec.split('/').collect::<Vec<&str>>();
+
+        let (package, dependency, feature) = match parts.as_slice() {
+            [package, dependency, feature] => (*package, *dependency, *feature),
+            [dependency, feature] => {
+                if document.is_workspace() {
+                    bail!(
+                        "{} is missing the package - use <package>/<dependency>/<feature>",
+                        spec
+                    )
+                }
+
+                let package = document
+                    .get_packages()
+                    .first()
+                    .context("no package found")?;
+
+                (package.name.as_str(), *dependency, *feature)
+            }
+            _ => bail!(
+                "could not parse {} - use <package>/<dependency>/<feature>",
+                spec
+            ),
+        };
+
+        let package = document.get_package_by_key(package)?;
+        let dependency = package.get_dep_by_key(dependency)?;
+
+        if dependency.get_feature(feature).is_none() {
+            bail!(
+                "could not find feature {} in {}/{}",
+                feature,
+                document.get_package_key(&package.name),
+                dependency.get_key()
+            )
+        }
+
+        Ok(FeatureSpec {
+            package_name: package.name.to_string(),
+            dependency_name: dependency.get_name(),
+            feature_name: feature.to_string(),
+        })
+    }
+}